@@ -0,0 +1,13 @@
+-- Replaces the game_started flag with the lobby state machine and tracks lobby activity for expiry
+ALTER TABLE gamelist
+    ADD COLUMN state TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    ADD COLUMN last_activity TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    ADD INDEX (state, last_activity);
+
+UPDATE gamelist SET state = CASE
+    WHEN game_started THEN 2
+    WHEN player1_id IS NOT NULL AND player2_id IS NOT NULL THEN 1
+    ELSE 0
+END;
+
+ALTER TABLE gamelist DROP COLUMN game_started;
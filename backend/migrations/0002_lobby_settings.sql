@@ -0,0 +1,5 @@
+-- Lobby settings, the backend now creates the game itself when a lobby fills up
+ALTER TABLE gamelist
+    ADD COLUMN width TINYINT UNSIGNED NOT NULL DEFAULT 7,
+    ADD COLUMN height TINYINT UNSIGNED NOT NULL DEFAULT 6,
+    ADD COLUMN win_length TINYINT UNSIGNED NOT NULL DEFAULT 4;
@@ -0,0 +1,7 @@
+-- Who moves first: chosen in the lobby settings and recorded per game
+ALTER TABLE gamelist
+    ADD COLUMN first_player TINYINT UNSIGNED NOT NULL DEFAULT 0;
+
+ALTER TABLE games
+    ADD COLUMN first_player_rule TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    ADD COLUMN first_player TINYINT UNSIGNED NOT NULL DEFAULT 1;
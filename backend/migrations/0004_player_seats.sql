@@ -0,0 +1,23 @@
+-- Games with up to 4 players: the player1_id/player2_id columns become JSON arrays of seats
+ALTER TABLE gamelist
+    ADD COLUMN seats TEXT NULL AFTER game_id,
+    ADD COLUMN num_players TINYINT UNSIGNED NOT NULL DEFAULT 2 AFTER win_length;
+
+UPDATE gamelist SET seats = JSON_ARRAY(player1_id, player2_id);
+
+ALTER TABLE gamelist
+    MODIFY COLUMN seats TEXT NOT NULL,
+    DROP COLUMN player1_id,
+    DROP COLUMN player2_id;
+
+ALTER TABLE games
+    ADD COLUMN player_ids TEXT NULL AFTER winning_chips,
+    ADD COLUMN eliminated TEXT NULL AFTER player_ids;
+
+UPDATE games SET player_ids = JSON_ARRAY(player1_id, player2_id), eliminated = '[]';
+
+ALTER TABLE games
+    MODIFY COLUMN player_ids TEXT NOT NULL,
+    MODIFY COLUMN eliminated TEXT NOT NULL,
+    DROP COLUMN player1_id,
+    DROP COLUMN player2_id;
@@ -0,0 +1,8 @@
+-- Move history for undo/takeback and the pending takeback request of a game
+ALTER TABLE games
+    ADD COLUMN moves TEXT NULL,
+    ADD COLUMN takeback_request TINYINT UNSIGNED NULL;
+
+UPDATE games SET moves = '[]';
+
+ALTER TABLE games MODIFY COLUMN moves TEXT NOT NULL;
@@ -0,0 +1,9 @@
+-- Pop Out variant. The move history now records pops and who made each move, so the history of
+-- running games is cleared: their moves from before the upgrade can no longer be taken back.
+ALTER TABLE gamelist
+    ADD COLUMN pop_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE games
+    ADD COLUMN pop_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE games SET moves = '[]', takeback_request = NULL;
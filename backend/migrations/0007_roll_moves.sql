@@ -0,0 +1,6 @@
+-- Roll moves variant: players may roll a row or column of the torus instead of dropping a chip
+ALTER TABLE gamelist
+    ADD COLUMN roll_moves BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE games
+    ADD COLUMN roll_moves BOOLEAN NOT NULL DEFAULT FALSE;
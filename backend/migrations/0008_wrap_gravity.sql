@@ -0,0 +1,9 @@
+-- Wrap gravity variant: chips can also be pushed in from the bottom edge of a column.
+-- Moves in the history now record the row a chip landed in, so the history of running games is cleared.
+ALTER TABLE gamelist
+    ADD COLUMN wrap_gravity BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE games
+    ADD COLUMN wrap_gravity BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE games SET moves = '[]', takeback_request = NULL;
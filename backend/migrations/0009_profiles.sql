@@ -0,0 +1,26 @@
+-- Player profiles with a display name, avatar colour, rating and record, and the history of finished games
+CREATE TABLE players (
+    player_id INT UNSIGNED NOT NULL PRIMARY KEY,
+    display_name VARCHAR(255) NULL,
+    avatar_color CHAR(7) NULL,
+    rating INT NOT NULL DEFAULT 1200,
+    wins INT UNSIGNED NOT NULL DEFAULT 0,
+    losses INT UNSIGNED NOT NULL DEFAULT 0,
+    draws INT UNSIGNED NOT NULL DEFAULT 0
+);
+
+CREATE TABLE finished_games (
+    finish_id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    game_id INT UNSIGNED NOT NULL,
+    game_name VARCHAR(255) NOT NULL,
+    finished_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE game_results (
+    finish_id INT UNSIGNED NOT NULL,
+    player_id INT UNSIGNED NOT NULL,
+    outcome TINYINT UNSIGNED NOT NULL,
+    rating_change INT NOT NULL,
+    PRIMARY KEY (finish_id, player_id),
+    INDEX (player_id, finish_id)
+);
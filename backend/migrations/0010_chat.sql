@@ -0,0 +1,11 @@
+-- Lobby and game chat, with separate channels for the players and for spectators
+CREATE TABLE chat_messages (
+    message_id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    game_id INT UNSIGNED NOT NULL,
+    channel TINYINT UNSIGNED NOT NULL,
+    sender_id INT UNSIGNED NOT NULL,
+    text TEXT NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX (game_id, channel, message_id),
+    INDEX (sender_id, sent_at)
+);
@@ -0,0 +1,3 @@
+-- Filters on the open games list: board size and win length
+ALTER TABLE gamelist
+    ADD INDEX (state, width, height, win_length);
@@ -0,0 +1,8 @@
+-- Time control of a lobby, and its creation time to sort the open games list on
+ALTER TABLE gamelist
+    ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- see TimeControl: 0 unlimited, 1 blitz, 2 rapid, 3 correspondence
+    ADD COLUMN time_control TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    ADD INDEX (state, created_at);
+
+UPDATE gamelist SET created_at = last_activity;
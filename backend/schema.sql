@@ -0,0 +1,92 @@
+-- Schema of connect_torus_db. Existing databases can be brought up to date with the scripts in migrations/
+
+CREATE TABLE IF NOT EXISTS gamelist (
+    game_id INT UNSIGNED NOT NULL PRIMARY KEY,
+    -- JSON array with the session id (or null) of each seat, the host sits in seat 0
+    seats TEXT NOT NULL,
+    game_name VARCHAR(255) NOT NULL,
+    -- see LobbyState: 0 open, 1 full, 2 started, 3 finished, 4 abandoned
+    state TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    last_activity TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- LobbySettings, used to create the game when the lobby fills up
+    width TINYINT UNSIGNED NOT NULL DEFAULT 7,
+    height TINYINT UNSIGNED NOT NULL DEFAULT 6,
+    win_length TINYINT UNSIGNED NOT NULL DEFAULT 4,
+    num_players TINYINT UNSIGNED NOT NULL DEFAULT 2,
+    -- see FirstPlayer: 0 host, 1 guest, 2 random, 3 alternate
+    first_player TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    -- rules::Variants: Pop Out, rolling rows and columns, and wrap gravity
+    pop_out BOOLEAN NOT NULL DEFAULT FALSE,
+    roll_moves BOOLEAN NOT NULL DEFAULT FALSE,
+    wrap_gravity BOOLEAN NOT NULL DEFAULT FALSE,
+    -- see TimeControl: 0 unlimited, 1 blitz, 2 rapid, 3 correspondence
+    time_control TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    INDEX (state, last_activity),
+    INDEX (state, created_at),
+    INDEX (state, width, height, win_length)
+);
+
+CREATE TABLE IF NOT EXISTS games (
+    game_id INT UNSIGNED NOT NULL PRIMARY KEY,
+    board TEXT NOT NULL,
+    win_length TINYINT UNSIGNED NOT NULL,
+    turn_player TINYINT UNSIGNED NOT NULL,
+    win_status TINYINT UNSIGNED NULL,
+    winning_chips TEXT NOT NULL,
+    -- JSON arrays with the session id of each player and the players that resigned
+    player_ids TEXT NOT NULL,
+    eliminated TEXT NOT NULL,
+    -- the lobby's FirstPlayer setting and the player (1 to 4) that moved first in the current game
+    first_player_rule TINYINT UNSIGNED NOT NULL DEFAULT 0,
+    first_player TINYINT UNSIGNED NOT NULL DEFAULT 1,
+    pop_out BOOLEAN NOT NULL DEFAULT FALSE,
+    roll_moves BOOLEAN NOT NULL DEFAULT FALSE,
+    wrap_gravity BOOLEAN NOT NULL DEFAULT FALSE,
+    -- JSON array of the moves played and who played them, and the player asking to take back their last move
+    moves TEXT NOT NULL,
+    takeback_request TINYINT UNSIGNED NULL
+);
+
+-- Profiles, keyed by session id. Players only get a row once they save a profile or finish a game.
+CREATE TABLE IF NOT EXISTS players (
+    player_id INT UNSIGNED NOT NULL PRIMARY KEY,
+    display_name VARCHAR(255) NULL,
+    -- "#rrggbb"
+    avatar_color CHAR(7) NULL,
+    rating INT NOT NULL DEFAULT 1200,
+    wins INT UNSIGNED NOT NULL DEFAULT 0,
+    losses INT UNSIGNED NOT NULL DEFAULT 0,
+    draws INT UNSIGNED NOT NULL DEFAULT 0
+);
+
+-- Every finished game, a rematch in the same lobby is a new row
+CREATE TABLE IF NOT EXISTS finished_games (
+    finish_id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    game_id INT UNSIGNED NOT NULL,
+    game_name VARCHAR(255) NOT NULL,
+    finished_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS game_results (
+    finish_id INT UNSIGNED NOT NULL,
+    player_id INT UNSIGNED NOT NULL,
+    -- see GameOutcome: 0 won, 1 lost, 2 draw
+    outcome TINYINT UNSIGNED NOT NULL,
+    rating_change INT NOT NULL,
+    PRIMARY KEY (finish_id, player_id),
+    INDEX (player_id, finish_id)
+);
+
+-- Chat of a lobby and the game started from it, see ChatChannel: 0 players, 1 spectators
+CREATE TABLE IF NOT EXISTS chat_messages (
+    message_id BIGINT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    game_id INT UNSIGNED NOT NULL,
+    channel TINYINT UNSIGNED NOT NULL,
+    sender_id INT UNSIGNED NOT NULL,
+    text TEXT NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX (game_id, channel, message_id),
+    -- for the rate limit
+    INDEX (sender_id, sent_at)
+);
@@ -1,24 +1,35 @@
+#![allow(clippy::let_unit_value)] // triggered by the route codegen for handlers returning ()
+#![allow(renamed_and_removed_lints)] // the FromForm derive still names the removed private_in_public lint
 #[macro_use]
 extern crate rocket;
-// use common::{board::Board, GameData, Player};
+use common::chat::{
+    ChatChannel, ChatMessage, ChatResult, NewChatMessage, RATE_LIMIT_MESSAGES, RATE_LIMIT_SECONDS,
+};
+use common::game::GameData;
+use common::lobby::{
+    GameList, GameLobby, JoinResult, LobbyAction, LobbyActionResult, LobbyFilter, LobbyPage,
+    LobbySettings, LobbySort, LobbyState, NewLobby, OpenLobby, TimeControl,
+};
+use common::profile::{
+    rating_changes, FinishedGame, GameOutcome, PlayerInfo, Profile, ProfileUpdate, Record,
+    START_RATING,
+};
+use common::rules::Variants;
+use common::{IdType, Player};
 use mysql::prelude::Queryable;
-use mysql::{params, Pool};
-use rocket::form::Form;
+use mysql::{params, Pool, Row, Transaction, TxOpts};
+use rocket::fairing::AdHoc;
 use rocket::fs::NamedFile;
 use rocket::http::{Cookie, CookieJar};
-use rocket::response::status::NotFound;
+use rocket::response::status::{BadRequest, NotFound};
 use rocket::serde::json::Json;
+use rocket::tokio::time::{interval, Duration};
 use rocket::Request;
 use rocket::State;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use std::fs;
 use std::path::PathBuf;
-use uiv2::Player; // uiv2 is now a lib which might be a bit of a hack
-                  // perhaps define GameData in common, then wrap it in ConnectGame in ui and implement component on that
-                  // in backend we can use GameData directly since we don't need to impl any traits on it
-                  // but wrapper classes are annoying and ugly
-use uiv2::connectgame::GameData;
-use uiv2::gamelist::{GameList, GameLobby};
-use uiv2::IdType;
 
 #[get("/")]
 async fn index(cookies: &CookieJar<'_>) -> Result<NamedFile, NotFound<String>> {
@@ -55,27 +66,47 @@ async fn redirect_ui(
     index(cookies).await
 }
 
-async fn get_lobbies(filter: &str, pool: &State<Pool>) -> Result<String, String> {
+/// Lobby from a row with the gamelist columns selected by `query_lobbies`
+fn lobby_from_row(row: &mut Row) -> GameLobby {
+    let state_num: u8 = row.take("state").unwrap();
+    let first_player_num: u8 = row.take("first_player").unwrap();
+    let time_control_num: u8 = row.take("time_control").unwrap();
+    GameLobby {
+        game_id: row.take("game_id").unwrap(),
+        seats: json_column(row, "seats"),
+        game_name: row.take("game_name").unwrap(),
+        state: state_num.try_into().unwrap(),
+        settings: LobbySettings {
+            width: row.take("width").unwrap(),
+            height: row.take("height").unwrap(),
+            win_length: row.take("win_length").unwrap(),
+            num_players: row.take("num_players").unwrap(),
+            first_player: first_player_num.try_into().unwrap(),
+            variants: Variants {
+                pop_out: row.take("pop_out").unwrap(),
+                roll_moves: row.take("roll_moves").unwrap(),
+                wrap_gravity: row.take("wrap_gravity").unwrap(),
+            },
+            time_control: time_control_num.try_into().unwrap(),
+        },
+    }
+}
+
+fn query_lobbies(filter: &str, pool: &Pool) -> Result<Vec<GameLobby>, String> {
     let query = &format!(
-        "SELECT game_id, player1_id, player2_id, game_name, game_started from gamelist where {}",
+        "SELECT game_id, seats, game_name, state, width, height, win_length, num_players, first_player, pop_out,
+        roll_moves, wrap_gravity, time_control from gamelist where {}",
         filter
     );
 
-    let mut conn = pool.inner().get_conn().expect("failed to connect to db");
-    let games = conn
-        .query_map(
-            query,
-            |(game_id, player1_id, player2_id, game_name, game_started)| GameLobby {
-                game_id,
-                player1_id,
-                player2_id,
-                game_name,
-                game_started,
-            },
-        )
-        .map_err(|_| "failed to get games from database")?;
+    let mut conn = pool.get_conn().expect("failed to connect to db");
+    conn.query_map(query, |mut row: Row| lobby_from_row(&mut row))
+        .map_err(|_| "failed to get games from database".to_owned())
+}
 
-    let gamelist = GameList { games: games };
+async fn get_lobbies(filter: &str, pool: &State<Pool>) -> Result<String, String> {
+    let games = query_lobbies(filter, pool.inner())?;
+    let gamelist = GameList { games };
 
     serde_json::to_string(&gamelist).map_err(|_| "serializing failed".to_owned())
 }
@@ -96,50 +127,160 @@ async fn getgamelist(pool: &State<Pool>) -> Result<String, String> {
     //     })
     //     .map_err(|_| "failed to get games from database")?;
 
-    // let gamelist = GameList { games: games };
+    // let gamelist = GameList { games };
 
     // serde_json::to_string(&gamelist).map_err(|_| "serializing failed".to_owned())
-    let filter = "player1_id is null or player2_id is null";
+    let filter = &format!("state = {}", u8::from(LobbyState::Open));
     get_lobbies(filter, pool).await
 }
 
-#[get("/get_joinable_lobbies/<player_id>")]
-async fn get_joinable_lobbies(player_id: IdType, pool: &State<Pool>) -> Result<String, String> {
-    let filter = &format!(
-        "(player1_id is null or player2_id is null) and (player1_id != {p} or player2_id != {p})",
-        p = player_id
-    );
-    get_lobbies(filter, pool).await
+/// Query parameters of `get_joinable_lobbies`, see `LobbyFilter`
+#[derive(FromForm)]
+struct LobbyQuery {
+    search: Option<String>,
+    width: Option<u8>,
+    height: Option<u8>,
+    win_length: Option<u8>,
+    time_control: Option<u8>,
+    min_rating: Option<i32>,
+    max_rating: Option<i32>,
+    sort: Option<u8>,
+    page: Option<u32>,
+}
+
+impl TryFrom<LobbyQuery> for LobbyFilter {
+    type Error = String;
+    fn try_from(query: LobbyQuery) -> Result<Self, Self::Error> {
+        Ok(LobbyFilter {
+            search: query.search.unwrap_or_default(),
+            width: query.width,
+            height: query.height,
+            win_length: query.win_length,
+            time_control: query.time_control.map(TimeControl::try_from).transpose()?,
+            min_rating: query.min_rating,
+            max_rating: query.max_rating,
+            sort: query
+                .sort
+                .map_or(Ok(LobbySort::default()), LobbySort::try_from)?,
+            page: query.page.unwrap_or(0),
+        })
+    }
+}
+
+/// Conditions of the open games list. Filters that are null match every lobby, the host is
+/// joined in as `p` so their rating and name can be filtered on.
+const OPEN_LOBBY_CONDITIONS: &str = "g.state = :open AND NOT JSON_CONTAINS(g.seats, :player)
+    AND (:width IS NULL OR g.width = :width)
+    AND (:height IS NULL OR g.height = :height)
+    AND (:win_length IS NULL OR g.win_length = :win_length)
+    AND (:time_control IS NULL OR g.time_control = :time_control)
+    AND (:min_rating IS NULL OR COALESCE(p.rating, :start_rating) >= :min_rating)
+    AND (:max_rating IS NULL OR COALESCE(p.rating, :start_rating) <= :max_rating)
+    AND (g.game_name LIKE :pattern OR p.display_name LIKE :pattern)";
+
+/// Open lobbies `player_id` can join, one page at a time
+fn query_lobby_page(
+    player_id: IdType,
+    filter: &LobbyFilter,
+    pool: &Pool,
+) -> Result<LobbyPage, mysql::Error> {
+    let mut conn = pool.get_conn()?;
+    let open = u8::from(LobbyState::Open);
+    // the search is matched literally, so LIKE's wildcards in it are escaped
+    let search = filter
+        .search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let order = match filter.sort {
+        LobbySort::Newest => "g.created_at DESC",
+        LobbySort::Oldest => "g.created_at ASC",
+        LobbySort::HighestRating => "host_rating DESC, g.created_at DESC",
+        LobbySort::LowestRating => "host_rating ASC, g.created_at DESC",
+    };
+    let from = "FROM gamelist g
+        LEFT JOIN players p ON p.player_id = CAST(JSON_EXTRACT(g.seats, '$[0]') AS UNSIGNED)";
+    let filter_params = || {
+        params! {
+            "open" => open,
+            "player" => player_id.to_string(),
+            "width" => filter.width,
+            "height" => filter.height,
+            "win_length" => filter.win_length,
+            "time_control" => filter.time_control.map(u8::from),
+            "start_rating" => START_RATING,
+            "min_rating" => filter.min_rating,
+            "max_rating" => filter.max_rating,
+            "pattern" => format!("%{}%", search),
+        }
+    };
+
+    let games = conn.exec_map(
+        format!(
+            "SELECT g.game_id, g.seats, g.game_name, g.state, g.width, g.height, g.win_length,
+                g.num_players, g.first_player, g.pop_out, g.roll_moves, g.wrap_gravity,
+                g.time_control, COALESCE(p.rating, {start}) AS host_rating
+            {from} WHERE {conditions} ORDER BY {order} LIMIT {limit} OFFSET {offset}",
+            start = START_RATING,
+            from = from,
+            conditions = OPEN_LOBBY_CONDITIONS,
+            order = order,
+            limit = LobbyFilter::PAGE_SIZE,
+            offset = u64::from(filter.page) * u64::from(LobbyFilter::PAGE_SIZE),
+        ),
+        filter_params(),
+        |mut row: Row| OpenLobby {
+            lobby: lobby_from_row(&mut row),
+            host_rating: row.take("host_rating").unwrap(),
+        },
+    )?;
+    let matching: Option<u32> = conn.exec_first(
+        format!("SELECT COUNT(*) {} WHERE {}", from, OPEN_LOBBY_CONDITIONS),
+        filter_params(),
+    )?;
+    let open_count: Option<u32> = conn.exec_first(
+        "SELECT COUNT(*) FROM gamelist WHERE state = :open",
+        params! {"open" => open},
+    )?;
+    Ok(LobbyPage {
+        games,
+        matching: matching.unwrap_or(0),
+        open: open_count.unwrap_or(0),
+    })
+}
+
+/// A page of the open lobbies `player_id` can join, with the number of lobbies matching the
+/// filter and of all open lobbies
+#[get("/get_joinable_lobbies/<player_id>?<query..>")]
+fn get_joinable_lobbies(
+    player_id: IdType,
+    query: LobbyQuery,
+    pool: &State<Pool>,
+) -> Result<Json<LobbyPage>, BadRequest<String>> {
+    let filter = LobbyFilter::try_from(query).map_err(|err| BadRequest(Some(err)))?;
+    query_lobby_page(player_id, &filter, pool.inner())
+        .map(Json)
+        .map_err(|err| BadRequest(Some(err.to_string())))
 }
 
 #[get("/get_joined_lobbies/<player_id>")]
 async fn get_joined_lobbies(player_id: IdType, pool: &State<Pool>) -> Result<String, String> {
-    let filter = &format!("player1_id = {p} or player2_id = {p}", p = player_id);
+    let filter = &format!(
+        "json_contains(seats, '{p}') and state in ({open}, {full}, {started})",
+        p = player_id,
+        open = u8::from(LobbyState::Open),
+        full = u8::from(LobbyState::Full),
+        started = u8::from(LobbyState::Started)
+    );
     get_lobbies(filter, pool).await
 }
 
 #[get("/gamelobby/<game_id>")]
 async fn getgamelobby(game_id: IdType, pool: &State<Pool>) -> Result<String, String> {
-    let query = format!(
-        "SELECT player1_id, player2_id, game_name, game_started from gamelist WHERE game_id = {}",
-        game_id
-    );
+    let games = query_lobbies(&format!("game_id = {}", game_id), pool.inner())?;
+    let gamelobby = games.first().ok_or("lobby not found")?;
 
-    let mut conn = pool.inner().get_conn().expect("failed to connect to db");
-    let games = conn
-        .query_map(
-            query,
-            |(player1_id, player2_id, game_name, game_started)| GameLobby {
-                game_id,
-                player1_id,
-                player2_id,
-                game_name,
-                game_started,
-            },
-        )
-        .map_err(|_| "failed to get games from database")?;
-
-    serde_json::to_string(&games[0]).map_err(|_| "serializing failed".to_owned())
+    serde_json::to_string(gamelobby).map_err(|_| "serializing failed".to_owned())
 }
 
 // #[derive(FromForm)]
@@ -150,43 +291,67 @@ async fn getgamelobby(game_id: IdType, pool: &State<Pool>) -> Result<String, Str
 // #[post("/create_game_lobby", data = "<form>")]
 // fn create_game_lobby(form: Form<CreateLobbyForm<'_>>, cookies: &CookieJar<'_>, pool: &State<Pool>) {
 
-#[post("/create_game_lobby", data = "<game_name>")]
-fn create_game_lobby(game_name: String, cookies: &CookieJar<'_>, pool: &State<Pool>) -> String {
-    // if cookies.get("session_id").is_none() {
-    //     cookies.add(Cookie::build("session_id", rand::random::<IdType>().to_string()).finish())
-    // }
-    let session_id_string = match cookies.get("session_id") {
-        Some(cookie) => cookie.value().to_owned(),
-        None => {
-            let random_id = rand::random::<IdType>().to_string();
-            cookies.add(Cookie::build("session_id", random_id.clone()).finish());
-            random_id
-        }
-    };
+/// Returns the session id stored in the cookies, creating a new one if there is none (or it is invalid)
+fn get_session_id(cookies: &CookieJar<'_>) -> IdType {
+    if let Some(session_id) = cookies
+        .get("session_id")
+        .and_then(|cookie| cookie.value().parse().ok())
+    {
+        return session_id;
+    }
+    let random_id = rand::random::<IdType>();
+    cookies.add(Cookie::build("session_id", random_id.to_string()).finish());
+    random_id
+}
+
+#[post("/create_game_lobby", data = "<new_lobby_json>")]
+fn create_game_lobby(
+    new_lobby_json: Json<NewLobby>,
+    cookies: &CookieJar<'_>,
+    pool: &State<Pool>,
+) -> Result<String, BadRequest<String>> {
+    let Json(NewLobby {
+        game_name,
+        settings,
+    }) = new_lobby_json;
+    if !settings.is_valid() {
+        return Err(BadRequest(Some("invalid lobby settings".to_owned())));
+    }
     let mut conn = pool.inner().get_conn().unwrap();
-    let session_id: IdType = session_id_string.parse().unwrap();
+    let session_id = get_session_id(cookies);
+    let mut seats = vec![None; settings.num_players as usize];
+    seats[0] = Some(session_id);
     let new_game_lobby = GameLobby {
         game_id: rand::random::<IdType>(),
-        player1_id: Some(session_id),
-        player2_id: None,
+        seats,
         // game_name: form.game_name.to_owned(),
-        game_name: game_name,
-        game_started: false,
+        game_name,
+        state: LobbyState::Open,
+        settings,
     };
 
-    conn.exec_drop("INSERT INTO gamelist (game_id, player1_id, player2_id, game_name, game_started) VALUES (:game_id, :player1_id, :player2_id, :game_name, :game_started)",
+    conn.exec_drop("INSERT INTO gamelist (game_id, seats, game_name, state, width, height, win_length, num_players, first_player, pop_out, roll_moves, wrap_gravity, time_control)
+        VALUES (:game_id, :seats, :game_name, :state, :width, :height, :win_length, :num_players, :first_player, :pop_out, :roll_moves,
+            :wrap_gravity, :time_control)",
      params! {"game_id" => new_game_lobby.game_id,
-                "player1_id" => new_game_lobby.player1_id, 
-                "player2_id" => new_game_lobby.player2_id,
+                "seats" => serde_json::to_string(&new_game_lobby.seats).unwrap(),
                 "game_name" => new_game_lobby.game_name,
-            "game_started"=> new_game_lobby.game_started}).unwrap();
+            "state"=> u8::from(new_game_lobby.state),
+            "width" => new_game_lobby.settings.width,
+            "height" => new_game_lobby.settings.height,
+            "win_length" => new_game_lobby.settings.win_length,
+            "num_players" => new_game_lobby.settings.num_players,
+            "first_player" => u8::from(new_game_lobby.settings.first_player),
+            "pop_out" => new_game_lobby.settings.variants.pop_out,
+            "roll_moves" => new_game_lobby.settings.variants.roll_moves,
+            "wrap_gravity" => new_game_lobby.settings.variants.wrap_gravity,
+            "time_control" => u8::from(new_game_lobby.settings.time_control)}).unwrap();
 
     println!(
         "Game_id comparison:\n{}\n{}",
-        new_game_lobby.game_id,
-        new_game_lobby.game_id.to_string()
+        new_game_lobby.game_id, new_game_lobby.game_id
     );
-    new_game_lobby.game_id.to_string()
+    Ok(new_game_lobby.game_id.to_string())
 }
 
 // #[derive(FromForm)]
@@ -209,68 +374,539 @@ fn create_game_lobby(game_name: String, cookies: &CookieJar<'_>, pool: &State<Po
 
 #[get("/getid")]
 fn getid(cookies: &CookieJar<'_>) -> String {
-    match cookies.get("session_id") {
-        Some(cookie) => cookie.value().to_owned(),
-        None => {
-            let random_id = rand::random::<IdType>().to_string();
-            cookies.add(Cookie::build("session_id", random_id.clone()).finish());
-            random_id
-        }
-    }
+    get_session_id(cookies).to_string()
 }
 
-#[get("/join/<game_id>")]
-fn join(game_id: IdType, pool: &State<Pool>, cookies: &CookieJar<'_>) -> String {
-    let mut conn = pool.inner().get_conn().unwrap();
-    match conn.exec_drop(
-        "UPDATE gamelist SET player2_id = :player2_id WHERE game_id=:game_id",
-        params! {"game_id" => game_id,
-        "player2_id" => cookies.get("session_id").unwrap().value()},
-    ) {
-        Ok(_) => "Succes!".to_owned(),
-        Err(_) => "Failed".to_owned(),
+/// seats (json), state, width, height, win_length, num_players, first_player, pop_out, roll_moves, wrap_gravity,
+/// time_control
+type LobbyRow = (String, u8, u8, u8, u8, u8, u8, bool, bool, bool, u8);
+
+/// Seats `player_id` in the first empty seat of the lobby. The lobby row is locked for the duration
+/// of the transaction, so two players joining at the same time cannot both get the same seat.
+/// Filling the last seat creates the game, so it is created exactly once.
+fn try_join(game_id: IdType, player_id: IdType, pool: &Pool) -> Result<JoinResult, mysql::Error> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    let lobby: Option<LobbyRow> = tx.exec_first(
+        "SELECT seats, state, width, height, win_length, num_players, first_player, pop_out, roll_moves, wrap_gravity,
+        time_control FROM gamelist WHERE game_id = :game_id FOR UPDATE",
+        params! {"game_id" => game_id},
+    )?;
+
+    let (
+        seats_json,
+        state_num,
+        width,
+        height,
+        win_length,
+        num_players,
+        first_player_num,
+        pop_out,
+        roll_moves,
+        wrap_gravity,
+        time_control_num,
+    ) = match lobby {
+        Some(lobby) => lobby,
+        None => return Ok(JoinResult::LobbyNotFound),
+    };
+    let mut seats: Vec<Option<IdType>> = serde_json::from_str(&seats_json).unwrap();
+    if seats.contains(&Some(player_id)) {
+        return Ok(JoinResult::AlreadyJoined);
+    }
+    match state_num.try_into().unwrap() {
+        LobbyState::Open => (),
+        LobbyState::Full => return Ok(JoinResult::LobbyFull),
+        LobbyState::Started => return Ok(JoinResult::GameStarted),
+        LobbyState::Finished | LobbyState::Abandoned => return Ok(JoinResult::LobbyClosed),
     }
+    match seats.iter_mut().find(|seat| seat.is_none()) {
+        Some(seat) => *seat = Some(player_id),
+        None => return Ok(JoinResult::LobbyFull),
+    }
+    let player_ids: Option<Vec<IdType>> = seats.iter().copied().collect();
+    let new_state = match player_ids {
+        Some(_) => LobbyState::Full,
+        None => LobbyState::Open,
+    };
+
+    tx.exec_drop(
+        "UPDATE gamelist SET seats = :seats, state = :state WHERE game_id = :game_id",
+        params! {
+            "game_id" => game_id,
+            "seats" => serde_json::to_string(&seats).unwrap(),
+            "state" => u8::from(new_state),
+        },
+    )?;
+    if let Some(player_ids) = player_ids {
+        let settings = LobbySettings {
+            width,
+            height,
+            win_length,
+            num_players,
+            first_player: first_player_num.try_into().unwrap(),
+            variants: Variants {
+                pop_out,
+                roll_moves,
+                wrap_gravity,
+            },
+            time_control: time_control_num.try_into().unwrap(),
+        };
+        start_game(&mut tx, game_id, player_ids, &settings)?;
+    }
+    tx.commit()?;
+    Ok(JoinResult::Joined)
 }
 
-#[post("/save_game", data = "<gamedata_json>")]
-fn save_game(gamedata_json: Json<GameData>, pool: &State<Pool>) {
-    let Json(gamedata) = gamedata_json;
-    println!("Received dat data: \n {:?}", &gamedata);
-    let mut conn = pool.inner().get_conn().unwrap();
+/// Inserts the game for a full lobby and marks the lobby as started
+fn start_game(
+    tx: &mut Transaction,
+    game_id: IdType,
+    player_ids: Vec<IdType>,
+    settings: &LobbySettings,
+) -> Result<(), mysql::Error> {
+    let gamedata = GameData::new(
+        settings.width,
+        settings.height,
+        settings.win_length,
+        game_id,
+        player_ids,
+        settings.first_player,
+        settings.variants,
+    );
     let turn_player_num: u8 = gamedata.turn_player.into();
     let win_status_num: Option<u8> = gamedata.win_status.map(Player::into);
-    conn.exec_drop(
-        "UPDATE games SET board = :board, turn_player = :turn_player, win_status = :win_status, winning_chips = :winning_chips WHERE game_id = :game_id",
-        params! {"board" => serde_json::to_string(&gamedata.board).unwrap(),
+    let first_player_num: u8 = gamedata.first_player.into();
+    tx.exec_drop(
+        "INSERT INTO games (
+            game_id, board, win_length, turn_player, win_status, winning_chips, player_ids, eliminated,
+            first_player_rule, first_player, pop_out, roll_moves, wrap_gravity, moves
+        ) VALUES (:game_id, :board, :win_length, :turn_player, :win_status, :winning_chips, :player_ids, :eliminated,
+            :first_player_rule, :first_player, :pop_out, :roll_moves, :wrap_gravity, :moves)",
+        params! {"game_id" => gamedata.game_id,
+        "board" => serde_json::to_string(&gamedata.board).unwrap(),
+        "win_length" => gamedata.win_length,
     "turn_player" => turn_player_num,
     "win_status" => win_status_num,
     "winning_chips" => serde_json::to_string(&gamedata.winning_chips).unwrap(),
-    "game_id" => gamedata.game_id})
-    .unwrap();
-    println!("Saved dat data");
+    "player_ids" => serde_json::to_string(&gamedata.player_ids).unwrap(),
+    "eliminated" => serde_json::to_string(&gamedata.eliminated).unwrap(),
+    "first_player_rule" => u8::from(gamedata.first_player_rule),
+    "first_player" => first_player_num,
+    "pop_out" => gamedata.variants.pop_out,
+    "roll_moves" => gamedata.variants.roll_moves,
+    "wrap_gravity" => gamedata.variants.wrap_gravity,
+    "moves" => serde_json::to_string(&gamedata.moves).unwrap()
+    })?;
+    tx.exec_drop(
+        "UPDATE gamelist SET state = :started WHERE game_id = :game_id",
+        params! {"started" => u8::from(LobbyState::Started), "game_id" => game_id},
+    )
 }
 
-#[post("/create_game", data = "<gamedata_json>")]
-fn create_game(gamedata_json: Json<GameData>, pool: &State<Pool>) {
-    println!("Received JSON: {:?}", gamedata_json);
-    let Json(gamedata) = gamedata_json;
-    let mut conn = pool.inner().get_conn().unwrap();
-    let turn_player_num: u8 = gamedata.turn_player.into();
-    let win_status_num: Option<u8> = gamedata.win_status.map(Player::into);
+#[post("/join/<game_id>")]
+fn join(game_id: IdType, pool: &State<Pool>, cookies: &CookieJar<'_>) -> Json<JoinResult> {
+    let player_id = get_session_id(cookies);
+    Json(
+        try_join(game_id, player_id, pool.inner()).unwrap_or_else(|err| {
+            println!("Failed to join game {}: {}", game_id, err);
+            JoinResult::Failed
+        }),
+    )
+}
+
+/// Leaving as a guest frees up their seat, leaving as the host cancels the lobby.
+fn try_lobby_action(
+    game_id: IdType,
+    player_id: IdType,
+    action: LobbyAction,
+    pool: &Pool,
+) -> Result<LobbyActionResult, mysql::Error> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    let lobby: Option<(String, u8)> = tx.exec_first(
+        "SELECT seats, state FROM gamelist WHERE game_id = :game_id FOR UPDATE",
+        params! {"game_id" => game_id},
+    )?;
+
+    let (seats_json, state_num) = match lobby {
+        Some(lobby) => lobby,
+        None => return Ok(LobbyActionResult::LobbyNotFound),
+    };
+    let mut seats: Vec<Option<IdType>> = serde_json::from_str(&seats_json).unwrap();
+    let own_seat = match seats.iter().position(|seat| *seat == Some(player_id)) {
+        Some(seat) => seat,
+        None => return Ok(LobbyActionResult::NotInLobby),
+    };
+    let is_host = own_seat == 0;
+    match state_num.try_into().unwrap() {
+        LobbyState::Open | LobbyState::Full => (),
+        LobbyState::Started => return Ok(LobbyActionResult::GameStarted),
+        LobbyState::Finished | LobbyState::Abandoned => return Ok(LobbyActionResult::LobbyClosed),
+    }
+
+    let new_state = match action {
+        LobbyAction::Leave | LobbyAction::Cancel if is_host => LobbyState::Abandoned,
+        LobbyAction::Leave => {
+            seats[own_seat] = None;
+            LobbyState::Open
+        }
+        LobbyAction::Kick(seat) if is_host => match seats.get_mut(seat) {
+            Some(guest @ Some(_)) if seat != 0 => {
+                *guest = None;
+                LobbyState::Open
+            }
+            _ => return Ok(LobbyActionResult::NoGuest),
+        },
+        _ => return Ok(LobbyActionResult::NotHost),
+    };
+    tx.exec_drop(
+        "UPDATE gamelist SET seats = :seats, state = :state WHERE game_id = :game_id",
+        params! {
+            "game_id" => game_id,
+            "seats" => serde_json::to_string(&seats).unwrap(),
+            "state" => u8::from(new_state),
+        },
+    )?;
+    tx.commit()?;
+    Ok(LobbyActionResult::Done)
+}
+
+fn lobby_action_response(
+    game_id: IdType,
+    action: LobbyAction,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Json<LobbyActionResult> {
+    let player_id = get_session_id(cookies);
+    Json(
+        try_lobby_action(game_id, player_id, action, pool.inner()).unwrap_or_else(|err| {
+            println!("Failed to update lobby {}: {}", game_id, err);
+            LobbyActionResult::Failed
+        }),
+    )
+}
+
+#[post("/leave/<game_id>")]
+fn leave(game_id: IdType, pool: &State<Pool>, cookies: &CookieJar<'_>) -> Json<LobbyActionResult> {
+    lobby_action_response(game_id, LobbyAction::Leave, pool, cookies)
+}
+
+#[post("/kick/<game_id>/<seat>")]
+fn kick(
+    game_id: IdType,
+    seat: usize,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Json<LobbyActionResult> {
+    lobby_action_response(game_id, LobbyAction::Kick(seat), pool, cookies)
+}
+
+#[post("/cancel/<game_id>")]
+fn cancel(game_id: IdType, pool: &State<Pool>, cookies: &CookieJar<'_>) -> Json<LobbyActionResult> {
+    lobby_action_response(game_id, LobbyAction::Cancel, pool, cookies)
+}
+
+/// Marks lobbies that nobody has joined, left or started for `idle_timeout` seconds as abandoned
+fn expire_idle_lobbies(pool: &Pool, idle_timeout: u64) -> Result<u64, mysql::Error> {
+    let mut conn = pool.get_conn()?;
     conn.exec_drop(
-        "INSERT INTO games (
-            game_id, board, win_length, turn_player, win_status, winning_chips, player1_id, player2_id
-        ) VALUES (:game_id, :board, :win_length, :turn_player, :win_status, :winning_chips, :player1_id, :player2_id)",
-        params! {"game_id" => gamedata.game_id,
-        "board" => serde_json::to_string(&gamedata.board).unwrap(),
-        "win_length" => gamedata.win_length,
+        "UPDATE gamelist SET state = :abandoned
+        WHERE state IN (:open, :full) AND last_activity < NOW() - INTERVAL :idle_timeout SECOND",
+        params! {
+            "abandoned" => u8::from(LobbyState::Abandoned),
+            "open" => u8::from(LobbyState::Open),
+            "full" => u8::from(LobbyState::Full),
+            "idle_timeout" => idle_timeout,
+        },
+    )?;
+    Ok(conn.affected_rows())
+}
+
+/// Read from rocket.toml, see the [default] profile there
+#[derive(Deserialize)]
+struct LobbyExpiryConfig {
+    lobby_idle_timeout: u64,
+    lobby_expiry_interval: u64,
+}
+
+fn lobby_expiry() -> AdHoc {
+    AdHoc::on_liftoff("Lobby expiry", |rocket| {
+        Box::pin(async move {
+            let config: LobbyExpiryConfig = rocket
+                .figment()
+                .extract()
+                .expect("lobby expiry settings missing from rocket.toml");
+            let pool = rocket.state::<Pool>().unwrap().clone();
+            rocket::tokio::spawn(async move {
+                let mut timer = interval(Duration::from_secs(config.lobby_expiry_interval));
+                loop {
+                    timer.tick().await;
+                    match expire_idle_lobbies(&pool, config.lobby_idle_timeout) {
+                        Ok(0) => (),
+                        Ok(expired) => println!("Expired {} idle lobbies", expired),
+                        Err(err) => println!("Failed to expire idle lobbies: {}", err),
+                    }
+                }
+            });
+        })
+    })
+}
+
+/// Writes the state of a running game, the settings it was created with stay as they are
+fn store_game(conn: &mut impl Queryable, gamedata: &GameData) -> Result<(), mysql::Error> {
+    let turn_player_num: u8 = gamedata.turn_player.clone().into();
+    let win_status_num: Option<u8> = gamedata.win_status.clone().map(Player::into);
+    let first_player_num: u8 = gamedata.first_player.clone().into();
+    let takeback_request_num: Option<u8> = gamedata.takeback_request.clone().map(Player::into);
+    conn.exec_drop(
+        "UPDATE games SET board = :board, turn_player = :turn_player, win_status = :win_status, winning_chips = :winning_chips,
+        eliminated = :eliminated, first_player = :first_player, moves = :moves, takeback_request = :takeback_request
+        WHERE game_id = :game_id",
+        params! {"board" => serde_json::to_string(&gamedata.board).unwrap(),
     "turn_player" => turn_player_num,
     "win_status" => win_status_num,
     "winning_chips" => serde_json::to_string(&gamedata.winning_chips).unwrap(),
-    "player1_id" => gamedata.player1_id,
-    "player2_id" => gamedata.player2_id
+    "eliminated" => serde_json::to_string(&gamedata.eliminated).unwrap(),
+    "first_player" => first_player_num,
+    "moves" => serde_json::to_string(&gamedata.moves).unwrap(),
+    "takeback_request" => takeback_request_num,
+    "game_id" => gamedata.game_id},
+    )
+}
+
+/// Takes over the caller's move, resignation or rematch from their copy of the game, see
+/// `GameData::apply_update`. Returns whether the change was allowed and saved.
+#[post("/save_game", data = "<gamedata_json>")]
+fn save_game(
+    gamedata_json: Json<GameData>,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Json<bool> {
+    let Json(update) = gamedata_json;
+    change_game_response(update.game_id, cookies, pool, |game, player| {
+        game.apply_update(&update, player)
     })
-    .unwrap();
+}
+
+/// Marks the lobby finished once the game ends, and records the result the moment it does.
+/// A rematch after a finished game reopens the lobby as started. Runs in the transaction that
+/// holds the lock on the game row, so the lobby always follows the saved game.
+fn update_lobby_after_save(tx: &mut Transaction, gamedata: &GameData) -> Result<(), mysql::Error> {
+    let lobby: Option<(u8, String)> = tx.exec_first(
+        "SELECT state, game_name FROM gamelist WHERE game_id = :game_id FOR UPDATE",
+        params! {"game_id" => gamedata.game_id},
+    )?;
+    let Some((state_num, game_name)) = lobby else {
+        return Ok(());
+    };
+    let old_state: LobbyState = state_num.try_into().unwrap();
+    if !matches!(old_state, LobbyState::Started | LobbyState::Finished) {
+        return Ok(());
+    }
+    let outcomes = gamedata.outcomes();
+    let new_state = match outcomes {
+        Some(_) => LobbyState::Finished,
+        None => LobbyState::Started,
+    };
+    tx.exec_drop(
+        "UPDATE gamelist SET state = :state WHERE game_id = :game_id",
+        params! {"state" => u8::from(new_state), "game_id" => gamedata.game_id},
+    )?;
+    if let (LobbyState::Started, Some(outcomes)) = (old_state, outcomes) {
+        record_finished_game(tx, gamedata, &game_name, &outcomes)?;
+    }
+    Ok(())
+}
+
+/// Stores the result of a game that just ended and updates the players' ratings and records
+fn record_finished_game(
+    tx: &mut Transaction,
+    gamedata: &GameData,
+    game_name: &str,
+    outcomes: &[GameOutcome],
+) -> Result<(), mysql::Error> {
+    tx.exec_batch(
+        "INSERT IGNORE INTO players (player_id) VALUES (:player_id)",
+        gamedata
+            .player_ids
+            .iter()
+            .map(|player_id| params! {"player_id" => player_id}),
+    )?;
+    let ratings = gamedata
+        .player_ids
+        .iter()
+        .map(|player_id| {
+            tx.exec_first(
+                "SELECT rating FROM players WHERE player_id = :player_id FOR UPDATE",
+                params! {"player_id" => player_id},
+            )
+            .map(Option::unwrap_or_default)
+        })
+        .collect::<Result<Vec<i32>, _>>()?;
+    let changes = rating_changes(&ratings, outcomes);
+
+    tx.exec_drop(
+        "INSERT INTO finished_games (game_id, game_name) VALUES (:game_id, :game_name)",
+        params! {"game_id" => gamedata.game_id, "game_name" => game_name},
+    )?;
+    let finish_id = tx.last_insert_id();
+    for ((player_id, outcome), change) in gamedata.player_ids.iter().zip(outcomes).zip(changes) {
+        tx.exec_drop(
+            "INSERT INTO game_results (finish_id, player_id, outcome, rating_change)
+            VALUES (:finish_id, :player_id, :outcome, :rating_change)",
+            params! {
+                "finish_id" => finish_id,
+                "player_id" => player_id,
+                "outcome" => u8::from(*outcome),
+                "rating_change" => change,
+            },
+        )?;
+        tx.exec_drop(
+            "UPDATE players SET rating = rating + :rating_change, wins = wins + :won,
+            losses = losses + :lost, draws = draws + :draw WHERE player_id = :player_id",
+            params! {
+                "rating_change" => change,
+                "won" => u8::from(*outcome == GameOutcome::Won),
+                "lost" => u8::from(*outcome == GameOutcome::Lost),
+                "draw" => u8::from(*outcome == GameOutcome::Draw),
+                "player_id" => player_id,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Name and avatar colour of each player in `player_ids`, in the same order
+fn query_player_infos(
+    conn: &mut impl Queryable,
+    player_ids: &[IdType],
+) -> Result<Vec<PlayerInfo>, mysql::Error> {
+    player_ids
+        .iter()
+        .map(|&player_id| {
+            let row: Option<(Option<String>, Option<String>)> = conn.exec_first(
+                "SELECT display_name, avatar_color FROM players WHERE player_id = :player_id",
+                params! {"player_id" => player_id},
+            )?;
+            Ok(match row {
+                Some((display_name, avatar_color)) => PlayerInfo {
+                    player_id,
+                    display_name,
+                    avatar_color,
+                },
+                None => PlayerInfo::anonymous(player_id),
+            })
+        })
+        .collect()
+}
+
+/// Most players `/api/players` looks up at once, a page of the game list
+const MAX_PLAYERS_PER_REQUEST: usize = LobbyFilter::PAGE_SIZE as usize;
+
+/// `player_ids` is a comma separated list, so a game or lobby needs a single request for all its players
+#[get("/players/<player_ids>")]
+fn players(
+    player_ids: &str,
+    pool: &State<Pool>,
+) -> Result<Json<Vec<PlayerInfo>>, BadRequest<String>> {
+    if player_ids.split(',').count() > MAX_PLAYERS_PER_REQUEST {
+        return Err(BadRequest(Some(format!(
+            "at most {} players can be looked up at once",
+            MAX_PLAYERS_PER_REQUEST
+        ))));
+    }
+    let player_ids = player_ids
+        .split(',')
+        .map(str::parse)
+        .collect::<Result<Vec<IdType>, _>>()
+        .map_err(|_| BadRequest(Some("invalid player id".to_owned())))?;
+    let mut conn = pool.inner().get_conn().unwrap();
+    query_player_infos(&mut conn, &player_ids)
+        .map(Json)
+        .map_err(|err| BadRequest(Some(err.to_string())))
+}
+
+/// Number of finished games shown on a profile
+const HISTORY_LENGTH: u32 = 20;
+
+fn query_profile(player_id: IdType, pool: &Pool) -> Result<Profile, mysql::Error> {
+    let mut conn = pool.get_conn()?;
+    let info = query_player_infos(&mut conn, &[player_id])?.remove(0);
+    let stats: Option<(i32, u32, u32, u32)> = conn.exec_first(
+        "SELECT rating, wins, losses, draws FROM players WHERE player_id = :player_id",
+        params! {"player_id" => player_id},
+    )?;
+    let (rating, wins, losses, draws) = stats.unwrap_or((START_RATING, 0, 0, 0));
+
+    let games: Vec<(u32, IdType, String, u8, i32, String)> = conn.exec(
+        "SELECT f.finish_id, f.game_id, f.game_name, r.outcome, r.rating_change,
+            DATE_FORMAT(f.finished_at, '%Y-%m-%d')
+        FROM game_results r JOIN finished_games f USING (finish_id)
+        WHERE r.player_id = :player_id ORDER BY f.finish_id DESC LIMIT :limit",
+        params! {"player_id" => player_id, "limit" => HISTORY_LENGTH},
+    )?;
+    let history = games
+        .into_iter()
+        .map(
+            |(finish_id, game_id, game_name, outcome_num, rating_change, finished_on)| {
+                let opponent_ids: Vec<IdType> = conn.exec(
+                    "SELECT player_id FROM game_results WHERE finish_id = :finish_id AND player_id != :player_id",
+                    params! {"finish_id" => finish_id, "player_id" => player_id},
+                )?;
+                Ok(FinishedGame {
+                    game_id,
+                    game_name,
+                    outcome: outcome_num.try_into().unwrap(),
+                    rating_change,
+                    opponents: query_player_infos(&mut conn, &opponent_ids)?,
+                    finished_on,
+                })
+            },
+        )
+        .collect::<Result<_, mysql::Error>>()?;
+
+    Ok(Profile {
+        info,
+        rating,
+        record: Record {
+            wins,
+            losses,
+            draws,
+        },
+        history,
+    })
+}
+
+#[get("/profile/<player_id>")]
+fn profile(player_id: IdType, pool: &State<Pool>) -> Result<Json<Profile>, NotFound<String>> {
+    query_profile(player_id, pool.inner())
+        .map(Json)
+        .map_err(|err| NotFound(err.to_string()))
+}
+
+/// Saves the name and avatar colour of the player making the request
+#[post("/profile", data = "<update_json>")]
+fn update_profile(
+    update_json: Json<ProfileUpdate>,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Result<(), BadRequest<String>> {
+    let Json(update) = update_json;
+    if !update.is_valid() {
+        return Err(BadRequest(Some("invalid profile".to_owned())));
+    }
+    let mut conn = pool.inner().get_conn().unwrap();
+    conn.exec_drop(
+        "INSERT INTO players (player_id, display_name, avatar_color)
+        VALUES (:player_id, :display_name, :avatar_color)
+        ON DUPLICATE KEY UPDATE display_name = VALUES(display_name), avatar_color = VALUES(avatar_color)",
+        params! {
+            "player_id" => get_session_id(cookies),
+            "display_name" => update.display_name,
+            "avatar_color" => update.avatar_color,
+        },
+    )
+    .map_err(|err| BadRequest(Some(err.to_string())))
 }
 
 // #[post("/create_game", data = "<gamedata_json>")]
@@ -278,52 +914,236 @@ fn create_game(gamedata_json: Json<GameData>, pool: &State<Pool>) {
 //     println!("{}", gamedata_json);
 // }
 
+/// Parses a column holding serialized json, like the board
+fn json_column<T: DeserializeOwned>(row: &mut Row, column: &str) -> T {
+    let json: String = row.take(column).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+/// Columns of the games table read by `game_from_row`
+const GAME_COLUMNS: &str =
+    "game_id, board, win_length, turn_player, win_status, winning_chips, player_ids, eliminated,
+    first_player_rule, first_player, pop_out, roll_moves, wrap_gravity, moves, takeback_request";
+
+fn game_from_row(row: &mut Row) -> GameData {
+    let turn_player_num: u8 = row.take("turn_player").unwrap();
+    let win_status_num: Option<u8> = row.take("win_status").unwrap();
+    let first_player_rule_num: u8 = row.take("first_player_rule").unwrap();
+    let first_player_num: u8 = row.take("first_player").unwrap();
+    let takeback_request_num: Option<u8> = row.take("takeback_request").unwrap();
+
+    GameData {
+        game_id: row.take("game_id").unwrap(),
+        board: json_column(row, "board"),
+        win_length: row.take("win_length").unwrap(),
+        turn_player: turn_player_num.try_into().unwrap(),
+        win_status: win_status_num.map(|num| num.try_into().unwrap()),
+        winning_chips: json_column(row, "winning_chips"),
+        player_ids: json_column(row, "player_ids"),
+        first_player_rule: first_player_rule_num.try_into().unwrap(),
+        first_player: first_player_num.try_into().unwrap(),
+        eliminated: json_column(row, "eliminated"),
+        variants: Variants {
+            pop_out: row.take("pop_out").unwrap(),
+            roll_moves: row.take("roll_moves").unwrap(),
+            wrap_gravity: row.take("wrap_gravity").unwrap(),
+        },
+        moves: json_column(row, "moves"),
+        takeback_request: takeback_request_num.map(|num| num.try_into().unwrap()),
+    }
+}
+
 #[get("/gamedata/<game_id>")]
 fn gamedata(game_id: IdType, pool: &State<Pool>) -> String {
     let mut conn = pool.inner().get_conn().unwrap();
     let results = conn
-        .query_map(
-            format!("SELECT * FROM games WHERE game_id = {}", game_id),
-            |(
-                game_id,
-                board_json,
-                win_length,
-                turn_player_num,
-                win_status_num,
-                winning_chips_json,
-                player1_id,
-                player2_id,
-            )| {
-                let board_json: String = board_json; // TODO hacks to satisfy type checker. Is there a better way?
-                let winning_chips_json: String = winning_chips_json;
-                let turn_player_num: u8 = turn_player_num;
-                let win_status_num: Option<u8> = win_status_num;
-
-                GameData {
-                    game_id,
-                    board: serde_json::from_str(board_json.as_str()).unwrap(),
-                    win_length,
-                    turn_player: turn_player_num.try_into().unwrap(),
-                    win_status: win_status_num.map(|num| num.try_into().unwrap()),
-                    winning_chips: serde_json::from_str(winning_chips_json.as_str()).unwrap(),
-                    player1_id,
-                    player2_id,
-                }
-            }, //     GameData {
-               //         game_id,
-               //         board: serde_json::from_str(&board_json).unwrap(),
-               //         win_length,
-               //         turn_player: turn_player_num.try_into().unwrap(),
-               //         win_status: winning_chips_json.map(|num| num.try_into().unwrap()),
-               //         winning_chips: serde_json::from_str(&winning_chips_json).unwrap(),
-               //         player1_id,
-               //         player2_id,
-
-               // }
+        .exec_map(
+            format!(
+                "SELECT {} FROM games WHERE game_id = :game_id",
+                GAME_COLUMNS
+            ),
+            params! {"game_id" => game_id},
+            |mut row: Row| game_from_row(&mut row),
         )
         .unwrap();
 
-    serde_json::to_string(&results.get(0)).unwrap()
+    serde_json::to_string(&results.first()).unwrap()
+}
+
+/// Runs `change` for the seat of `player_id` on the stored game and saves the game if it returns
+/// true, along with the lobby state it leads to. The game row is locked meanwhile, so two players
+/// can't both act on the same state.
+fn change_game(
+    game_id: IdType,
+    player_id: IdType,
+    pool: &Pool,
+    change: impl FnOnce(&mut GameData, Player) -> bool,
+) -> Result<bool, mysql::Error> {
+    let mut conn = pool.get_conn()?;
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    let mut games = tx.exec_map(
+        format!(
+            "SELECT {} FROM games WHERE game_id = :game_id FOR UPDATE",
+            GAME_COLUMNS
+        ),
+        params! {"game_id" => game_id},
+        |mut row: Row| game_from_row(&mut row),
+    )?;
+    let Some(mut game) = games.pop() else {
+        return Ok(false);
+    };
+    let Some(player) = game.seat_of(player_id) else {
+        return Ok(false);
+    };
+    if !change(&mut game, player) {
+        return Ok(false);
+    }
+    store_game(&mut tx, &game)?;
+    update_lobby_after_save(&mut tx, &game)?;
+    tx.commit()?;
+    Ok(true)
+}
+
+fn change_game_response(
+    game_id: IdType,
+    cookies: &CookieJar<'_>,
+    pool: &State<Pool>,
+    change: impl FnOnce(&mut GameData, Player) -> bool,
+) -> Json<bool> {
+    let player_id = get_session_id(cookies);
+    Json(
+        change_game(game_id, player_id, pool.inner(), change).unwrap_or_else(|err| {
+            println!("Failed to update game {}: {}", game_id, err);
+            false
+        }),
+    )
+}
+
+/// Asks the other players to take back the caller's last move. Returns whether the request was made.
+#[post("/takeback/<game_id>")]
+fn request_takeback(game_id: IdType, pool: &State<Pool>, cookies: &CookieJar<'_>) -> Json<bool> {
+    change_game_response(game_id, cookies, pool, |game, player| {
+        game.request_takeback(player)
+    })
+}
+
+/// Accepts or declines the pending takeback request. Only another player than the one that asked
+/// can answer, accepting takes the move back. Returns whether the answer was taken.
+#[post("/answer_takeback/<game_id>/<accept>")]
+fn answer_takeback(
+    game_id: IdType,
+    accept: bool,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Json<bool> {
+    change_game_response(game_id, cookies, pool, |game, player| {
+        game.answer_takeback(&player, accept)
+    })
+}
+
+/// Most messages sent to a client that joins a chat, older ones are left out
+const CHAT_BACKLOG: u32 = 100;
+
+/// Messages in a channel after `after`, oldest first. Clients poll with the id of the last message they have.
+#[get("/chat/<game_id>/<channel_num>/<after>")]
+fn chat_messages(
+    game_id: IdType,
+    channel_num: u8,
+    after: u64,
+    pool: &State<Pool>,
+) -> Result<Json<Vec<ChatMessage>>, BadRequest<String>> {
+    let channel: ChatChannel = channel_num
+        .try_into()
+        .map_err(|err| BadRequest(Some(err)))?;
+    let mut conn = pool.inner().get_conn().unwrap();
+    let mut messages = conn
+        .exec_map(
+            "SELECT m.message_id, m.sender_id, p.display_name, p.avatar_color, m.text,
+                DATE_FORMAT(m.sent_at, '%H:%i')
+            FROM chat_messages m LEFT JOIN players p ON p.player_id = m.sender_id
+            WHERE m.game_id = :game_id AND m.channel = :channel AND m.message_id > :after
+            ORDER BY m.message_id DESC LIMIT :backlog",
+            params! {
+                "game_id" => game_id,
+                "channel" => channel_num,
+                "after" => after,
+                "backlog" => CHAT_BACKLOG,
+            },
+            |(message_id, player_id, display_name, avatar_color, text, sent_at)| ChatMessage {
+                message_id,
+                channel,
+                sender: PlayerInfo {
+                    player_id,
+                    display_name,
+                    avatar_color,
+                },
+                text,
+                sent_at,
+            },
+        )
+        .map_err(|err| BadRequest(Some(err.to_string())))?;
+    messages.reverse();
+    Ok(Json(messages))
+}
+
+/// Players write in the players' channel of their own lobby, everyone else in the spectators' channel
+fn try_send_chat(
+    game_id: IdType,
+    sender_id: IdType,
+    message: NewChatMessage,
+    pool: &Pool,
+) -> Result<ChatResult, mysql::Error> {
+    if !message.is_valid() {
+        return Ok(ChatResult::InvalidMessage);
+    }
+    let mut conn = pool.get_conn()?;
+    let seats_json: Option<String> = conn.exec_first(
+        "SELECT seats FROM gamelist WHERE game_id = :game_id",
+        params! {"game_id" => game_id},
+    )?;
+    let Some(seats_json) = seats_json else {
+        return Ok(ChatResult::LobbyNotFound);
+    };
+    let seats: Vec<Option<IdType>> = serde_json::from_str(&seats_json).unwrap();
+    if ChatChannel::for_sender(seats.contains(&Some(sender_id))) != message.channel {
+        return Ok(ChatResult::WrongChannel);
+    }
+    let recent: Option<u32> = conn.exec_first(
+        "SELECT COUNT(*) FROM chat_messages
+        WHERE sender_id = :sender_id AND sent_at > NOW() - INTERVAL :seconds SECOND",
+        params! {"sender_id" => sender_id, "seconds" => RATE_LIMIT_SECONDS},
+    )?;
+    if recent.unwrap_or_default() >= RATE_LIMIT_MESSAGES {
+        return Ok(ChatResult::RateLimited);
+    }
+    conn.exec_drop(
+        "INSERT INTO chat_messages (game_id, channel, sender_id, text)
+        VALUES (:game_id, :channel, :sender_id, :text)",
+        params! {
+            "game_id" => game_id,
+            "channel" => u8::from(message.channel),
+            "sender_id" => sender_id,
+            "text" => message.text.trim(),
+        },
+    )?;
+    Ok(ChatResult::Sent)
+}
+
+#[post("/chat/<game_id>", data = "<message_json>")]
+fn send_chat(
+    game_id: IdType,
+    message_json: Json<NewChatMessage>,
+    pool: &State<Pool>,
+    cookies: &CookieJar<'_>,
+) -> Json<ChatResult> {
+    let Json(message) = message_json;
+    let sender_id = get_session_id(cookies);
+    Json(
+        try_send_chat(game_id, sender_id, message, pool.inner()).unwrap_or_else(|err| {
+            println!("Failed to send chat message in {}: {}", game_id, err);
+            ChatResult::Failed
+        }),
+    )
 }
 
 #[launch]
@@ -344,14 +1164,24 @@ fn rocket() -> _ {
                 create_game_lobby,
                 join,
                 getid,
-                create_game,
                 save_game,
                 gamedata,
                 getgamelobby,
                 get_joinable_lobbies,
-                get_joined_lobbies
+                get_joined_lobbies,
+                request_takeback,
+                answer_takeback,
+                leave,
+                kick,
+                cancel,
+                players,
+                profile,
+                update_profile,
+                chat_messages,
+                send_chat
             ],
         ) //
         .manage(pool)
+        .attach(lobby_expiry())
         .register("/", catchers![not_found])
 }
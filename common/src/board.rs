@@ -0,0 +1,416 @@
+use crate::Player;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+fn modulo(n: i32, m: i32) -> i32 {
+    // always returns in range [0,m)
+    (n % m + m) % m
+}
+
+#[derive(Debug, Clone)]
+pub struct InsertError;
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Board {
+    pub board: Vec<Vec<Option<Player>>>,
+    pub width: u8,
+    pub height: u8,
+}
+
+#[cfg(test)]
+impl Board {
+    /// Board drawn as text, top row first: '.' is an empty cell and '1' to '4' a player's chip
+    pub(crate) fn from_rows(rows: &[&str]) -> Self {
+        let width = rows[0].len();
+        let mut board = Board::new(width as u8, rows.len() as u8);
+        for (row, line) in rows.iter().rev().enumerate() {
+            for (col, cell) in line.chars().enumerate() {
+                board.board[col][row] = cell
+                    .to_digit(10)
+                    .and_then(|num| Player::try_from(num as u8).ok());
+            }
+        }
+        board
+    }
+}
+
+impl Board {
+    pub fn new(width: u8, height: u8) -> Self {
+        let mut empty_col = Vec::new();
+        for _ in 0..height {
+            empty_col.push(None);
+        }
+        let mut board = Vec::new();
+        for _ in 0..width {
+            board.push(empty_col.clone());
+        }
+        Board {
+            board,
+            width,
+            height,
+        }
+    }
+
+    /// Drops a chip in `column`, it falls to the lowest empty cell. Fails if the column is full or
+    /// not on the board.
+    pub fn insert(&mut self, column: usize, player: &Player) -> Result<usize, InsertError> {
+        let cells = self.board.get_mut(column).ok_or(InsertError)?;
+        let row = cells.iter().position(Option::is_none).ok_or(InsertError)?;
+        cells[row] = Some(player.clone());
+        Ok(row)
+    }
+
+    /// Wrap gravity: pushes a chip in from the bottom edge, it rises until it hits a chip or the top edge
+    pub fn insert_from_bottom(
+        &mut self,
+        column: usize,
+        player: &Player,
+    ) -> Result<usize, InsertError> {
+        let cells = self.board.get_mut(column).ok_or(InsertError)?;
+        let row = cells.iter().rposition(Option::is_none).ok_or(InsertError)?;
+        cells[row] = Some(player.clone());
+        Ok(row)
+    }
+
+    /// Removes the bottom chip of `column`, the chips resting on it fall down one row
+    pub fn pop_bottom(&mut self, column: usize) -> Option<Player> {
+        let cells = self.board.get_mut(column)?;
+        // with wrap gravity there can be chips hanging from the top edge, those stay put
+        let stack_height = cells
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(cells.len());
+        let bottom = cells.first_mut()?.take()?;
+        cells[..stack_height].rotate_left(1);
+        Some(bottom)
+    }
+
+    /// Shifts `column` one cell up (towards higher rows) or down, wrapping around
+    pub fn roll_column(&mut self, column: usize, up: bool) -> Result<(), InsertError> {
+        let cells = self.board.get_mut(column).ok_or(InsertError)?;
+        match up {
+            true => cells.rotate_right(1),
+            false => cells.rotate_left(1),
+        }
+        Ok(())
+    }
+
+    /// Shifts `row` one cell to the right (towards higher columns) or left, wrapping around
+    pub fn roll_row(&mut self, row: usize, right: bool) -> Result<(), InsertError> {
+        if row >= self.height as usize {
+            return Err(InsertError);
+        }
+        let mut cells: Vec<Option<Player>> = self
+            .board
+            .iter_mut()
+            .map(|column| column[row].take())
+            .collect();
+        match right {
+            true => cells.rotate_right(1),
+            false => cells.rotate_left(1),
+        }
+        for (column, cell) in self.board.iter_mut().zip(cells) {
+            column[row] = cell;
+        }
+        Ok(())
+    }
+
+    /// Whether every chip rests on the bottom or on another chip. With `wrap_gravity` chips may
+    /// also hang from the top edge, so the empty cells of a column just have to be in one piece.
+    pub fn is_settled(&self, wrap_gravity: bool) -> bool {
+        self.board.iter().all(|column| {
+            let mut after_stack = column.iter().skip_while(|cell| cell.is_some());
+            match wrap_gravity {
+                true => after_stack
+                    .skip_while(|cell| cell.is_none())
+                    .all(Option::is_some),
+                false => after_stack.all(Option::is_none),
+            }
+        })
+    }
+
+    /// Pushes a chip in at the bottom of `column`, undoing a `pop_bottom`
+    pub fn push_bottom(&mut self, column: usize, player: &Player) -> Result<(), InsertError> {
+        let cells = self.board.get_mut(column).ok_or(InsertError)?;
+        let stack_height = cells.iter().position(Option::is_none).ok_or(InsertError)?;
+        cells[..=stack_height].rotate_right(1);
+        cells[0] = Some(player.clone());
+        Ok(())
+    }
+
+    /// Whether every cell holds a chip
+    pub fn is_full(&self) -> bool {
+        self.board.iter().flatten().all(Option::is_some)
+    }
+
+    #[allow(dead_code)] //TODO: remove function if not necessary
+    fn column_full(&self, colnr: usize) -> bool {
+        self.board[colnr].last().is_some()
+    }
+
+    fn shift_coords(
+        &self,
+        (col, row): (usize, usize),
+        (dx, dy): (i32, i32),
+        amount: i32,
+    ) -> (usize, usize) {
+        let pre_x = modulo(col as i32 + amount * dx, self.width as i32);
+        let pre_y = modulo(row as i32 + amount * dy, self.height as i32);
+        // log::info!("{}", format!("{} {}", pre_x, pre_y));
+        let x: usize = pre_x.try_into().unwrap();
+        let y: usize = pre_y.try_into().unwrap();
+        (x, y)
+    }
+
+    fn check_line(
+        &self,
+        col: usize,
+        row: usize,
+        dx: i32,
+        dy: i32,
+        player: &Player,
+        win_length: usize,
+    ) -> bool {
+        // Note that this does not check for duplicates in the same line
+        // i.e. if the board is smaller than the win_length in some direction
+        // filling up that direction with one color will be a win even though it's shorter than win_length
+        let mut consecutive = 0;
+        for s in -(win_length as i32)..(win_length as i32) {
+            let (x, y) = self.shift_coords((col, row), (dx, dy), s);
+            let cell_status = self.board[x][y].clone(); // why clone?
+            if cell_status == Some(player.clone()) {
+                consecutive += 1;
+            } else {
+                consecutive = 0;
+            }
+
+            if consecutive >= win_length {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn check_win(
+        // returns bool. If needed, switch back to returning winning direction if present
+        &self,
+        col: usize,
+        row: usize,
+        player: &Player,
+        win_length: usize,
+    ) -> bool {
+        let directions = vec![(1, 1), (1, 0), (1, -1), (0, -1)];
+        for (dx, dy) in directions {
+            if self.check_line(col, row, dx, dy, player, win_length) {
+                return true;
+            }
+            // log::info!("Checked line!");
+        }
+        false
+    }
+
+    /// Chips of `player` in an unbroken run through `(col, row)` in `direction`, wrapping around the
+    /// edges. The run is a winning line if it holds at least `win_length` chips.
+    pub fn find_adjacent_chips_in_dir(
+        &self,
+        col: usize,
+        row: usize,
+        direction: (i32, i32),
+        player: &Player,
+    ) -> HashSet<(usize, usize)> {
+        let mut found_positions = HashSet::new();
+        let mut s = 0;
+        loop {
+            // first find all consecutive
+            let (x, y) = self.shift_coords((col, row), direction, s);
+            if found_positions.contains(&(x, y)) {
+                // if we loop all the way around, we can return
+                // TODO: this actually isn't necessary: this situation only occurs when a full "loop"
+                // (aka row/column/snake through diagonals) is filled, however, if that loop is only missing 1 spot,
+                // there is already a winning connect line as long as every loop is at least one longer than the win-length
+                // (having the width and height > win_length guarantees this is the case), hence you can never complete the
+                // loop
+                // Keeping the HashSet is still useful though, since we use it to look up if a chip is winning
+                return found_positions;
+            }
+            if self.board[x][y].as_ref() == Some(player) {
+                found_positions.insert((x, y));
+            } else {
+                break; // consecutive chain broken
+            }
+            s += 1;
+        }
+        s = -1;
+        loop {
+            let (x, y) = self.shift_coords((col, row), direction, s);
+            // since we did not return yet, we know there is one opponent chip/empty spot in the line
+            // so we don't need to check whether we loop around
+            if self.board[x][y].as_ref() == Some(player) {
+                found_positions.insert((x, y));
+            } else {
+                break;
+            }
+            s -= 1;
+        }
+        found_positions
+    }
+
+    pub fn find_winning_chips(
+        &self,
+        col: usize,
+        row: usize,
+        player: &Player,
+        win_length: usize,
+    ) -> HashSet<(usize, usize)> {
+        let directions = vec![(1, 1), (1, 0), (1, -1), (0, -1)]; // TODO copied code
+        let mut found_positions = HashSet::new();
+        for dir in directions {
+            let adj_chips_in_dir = self.find_adjacent_chips_in_dir(col, row, dir, player);
+            if adj_chips_in_dir.len() >= win_length {
+                // in this case the adjacent chips are a winning line
+                // TODO note that now the check_line and check win methods are essentially redundant,
+                // (except for being somewhat more efficient?)
+                found_positions.extend(self.find_adjacent_chips_in_dir(col, row, dir, player));
+            }
+        }
+        found_positions
+    }
+
+    /// Empty cells where `player` would complete a line of `win_length` with their next chip. Only
+    /// cells a chip can land in count: the lowest empty cell of each column, and with `wrap_gravity`
+    /// also the highest one, where a chip pushed in from the bottom ends up. Lines wrap around the
+    /// edges as usual.
+    pub fn threats(
+        &self,
+        player: &Player,
+        win_length: usize,
+        wrap_gravity: bool,
+    ) -> HashSet<(usize, usize)> {
+        let mut board = self.clone();
+        let mut threats = HashSet::new();
+        for (col, column) in self.board.iter().enumerate() {
+            let lowest = column.iter().position(Option::is_none);
+            let highest = column
+                .iter()
+                .rposition(Option::is_none)
+                .filter(|_| wrap_gravity);
+            for row in lowest.into_iter().chain(highest) {
+                board.board[col][row] = Some(player.clone());
+                if board.check_win(col, row, player, win_length) {
+                    threats.insert((col, row));
+                }
+                board.board[col][row] = None;
+            }
+        }
+        threats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_stacks_from_the_bottom() {
+        let mut board = Board::new(2, 3);
+        assert_eq!(board.insert(1, &Player::One).unwrap(), 0);
+        assert_eq!(board.insert(1, &Player::Two).unwrap(), 1);
+        assert_eq!(board.insert(1, &Player::One).unwrap(), 2);
+        assert!(board.insert(1, &Player::Two).is_err());
+        assert_eq!(board, Board::from_rows(&[".1", ".2", ".1"]));
+    }
+
+    #[test]
+    fn insert_outside_the_board_fails() {
+        let mut board = Board::new(2, 3);
+        assert!(board.insert(2, &Player::One).is_err());
+        assert!(board.insert_from_bottom(2, &Player::One).is_err());
+        assert_eq!(board, Board::new(2, 3));
+    }
+
+    #[test]
+    fn insert_from_bottom_rises_until_it_hits_a_chip() {
+        let mut board = Board::new(1, 3);
+        assert_eq!(board.insert_from_bottom(0, &Player::One).unwrap(), 2);
+        assert_eq!(board.insert(0, &Player::Two).unwrap(), 0);
+        // the chips from both ends meet in the middle
+        assert_eq!(board.insert_from_bottom(0, &Player::Three).unwrap(), 1);
+        assert!(board.insert_from_bottom(0, &Player::One).is_err());
+        assert_eq!(board, Board::from_rows(&["1", "3", "2"]));
+    }
+
+    #[test]
+    fn pop_bottom_leaves_chips_hanging_from_the_top() {
+        let before = Board::from_rows(&["2", ".", "2", "1"]);
+        let mut board = before.clone();
+        assert_eq!(board.pop_bottom(0), Some(Player::One));
+        assert_eq!(board, Board::from_rows(&["2", ".", ".", "2"]));
+        board.push_bottom(0, &Player::One).unwrap();
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn pop_bottom_of_an_empty_column() {
+        let mut board = Board::new(1, 2);
+        assert_eq!(board.pop_bottom(0), None);
+        assert_eq!(board.pop_bottom(1), None);
+    }
+
+    #[test]
+    fn settled_boards() {
+        let stacked = Board::from_rows(&["..", "1.", "21"]);
+        assert!(stacked.is_settled(false));
+        assert!(stacked.is_settled(true));
+
+        let hanging = Board::from_rows(&["1", ".", "2"]);
+        assert!(!hanging.is_settled(false));
+        assert!(hanging.is_settled(true));
+
+        let floating = Board::from_rows(&[".", "1", "."]);
+        assert!(!floating.is_settled(false));
+        assert!(!floating.is_settled(true));
+    }
+
+    #[test]
+    fn rolls_wrap_around() {
+        let mut board = Board::from_rows(&["...", "12."]);
+        board.roll_row(0, true).unwrap();
+        assert_eq!(board, Board::from_rows(&["...", ".12"]));
+        board.roll_row(0, true).unwrap();
+        assert_eq!(board, Board::from_rows(&["...", "2.1"]));
+        board.roll_column(0, true).unwrap();
+        assert_eq!(board, Board::from_rows(&["2..", "..1"]));
+        assert!(board.roll_row(2, true).is_err());
+        assert!(board.roll_column(3, true).is_err());
+    }
+
+    #[test]
+    fn lines_wrap_around_the_edges() {
+        let board = Board::from_rows(&["....", "1.11"]);
+        assert!(board.check_win(0, 0, &Player::One, 3));
+        assert!(!board.check_win(0, 0, &Player::One, 4));
+        let expected: HashSet<_> = [(2, 0), (3, 0), (0, 0)].into_iter().collect();
+        assert_eq!(board.find_winning_chips(0, 0, &Player::One, 3), expected);
+    }
+
+    #[test]
+    fn winning_chips_leave_out_shorter_runs() {
+        let board = Board::from_rows(&["....", "1...", "111."]);
+        let expected: HashSet<_> = [(0, 0), (1, 0), (2, 0)].into_iter().collect();
+        assert_eq!(board.find_winning_chips(0, 0, &Player::One, 3), expected);
+    }
+
+    #[test]
+    fn threats_are_cells_a_chip_can_land_in() {
+        let board = Board::from_rows(&["....", "....", "11.."]);
+        let expected: HashSet<_> = [(2, 0), (3, 0)].into_iter().collect();
+        assert_eq!(board.threats(&Player::One, 3, false), expected);
+        assert!(board.threats(&Player::Two, 3, false).is_empty());
+
+        // the line in the top row can only be completed by a chip pushed in from the bottom
+        let hanging = Board::from_rows(&["11..", "....", "22.."]);
+        assert!(hanging.threats(&Player::One, 3, false).is_empty());
+        let expected: HashSet<_> = [(2, 2), (3, 2)].into_iter().collect();
+        assert_eq!(hanging.threats(&Player::One, 3, true), expected);
+    }
+}
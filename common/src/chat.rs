@@ -0,0 +1,86 @@
+use crate::profile::PlayerInfo;
+use serde::{Deserialize, Serialize};
+
+/// Longest message that can be sent, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 280;
+/// A player can send at most this many messages per `RATE_LIMIT_SECONDS`
+pub const RATE_LIMIT_MESSAGES: u32 = 5;
+pub const RATE_LIMIT_SECONDS: u32 = 10;
+
+/// Every lobby and game has two channels. The seated players talk in one, everyone else in the
+/// other, so spectators can't flood the players' chat. Both can be read by anyone.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum ChatChannel {
+    Players,
+    Spectators,
+}
+
+impl ChatChannel {
+    pub const ALL: [ChatChannel; 2] = [ChatChannel::Players, ChatChannel::Spectators];
+
+    /// The channel someone can write in
+    pub fn for_sender(seated: bool) -> Self {
+        if seated {
+            ChatChannel::Players
+        } else {
+            ChatChannel::Spectators
+        }
+    }
+}
+
+impl From<ChatChannel> for u8 {
+    fn from(channel: ChatChannel) -> u8 {
+        match channel {
+            ChatChannel::Players => 0,
+            ChatChannel::Spectators => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for ChatChannel {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ChatChannel::Players),
+            1 => Ok(ChatChannel::Spectators),
+            _ => Err("Invalid chat channel identifier".to_owned()),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct ChatMessage {
+    /// Increases with every message sent, so clients can ask for the messages after the last one they have
+    pub message_id: u64,
+    pub channel: ChatChannel,
+    pub sender: PlayerInfo,
+    pub text: String,
+    /// Time the message was sent, as "HH:MM"
+    pub sent_at: String,
+}
+
+/// Sent to post a message, the backend checks the sender may write in `channel`
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct NewChatMessage {
+    pub channel: ChatChannel,
+    pub text: String,
+}
+
+impl NewChatMessage {
+    pub fn is_valid(&self) -> bool {
+        !self.text.trim().is_empty() && self.text.chars().count() <= MAX_MESSAGE_LENGTH
+    }
+}
+
+/// Why a message was (not) sent
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum ChatResult {
+    Sent,
+    /// Empty or longer than `MAX_MESSAGE_LENGTH`
+    InvalidMessage,
+    /// Spectators can't write in the players' channel and players don't write in the spectators'
+    WrongChannel,
+    RateLimited,
+    LobbyNotFound,
+    Failed,
+}
@@ -0,0 +1,398 @@
+use crate::board::{Board, InsertError};
+use crate::lobby::FirstPlayer;
+use crate::profile::GameOutcome;
+use crate::rules::{Move, PlayedMove, Variants};
+use crate::{rules, IdType, Player};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct GameData {
+    // TODO change usizes to fixed size (in Board too!)
+    pub game_id: IdType,
+    pub board: Board,
+    pub win_length: u8,
+    pub turn_player: Player,
+    pub win_status: Option<Player>,
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    /// Session ids of the players, indexed by `Player::index`
+    pub player_ids: Vec<IdType>,
+    pub first_player_rule: FirstPlayer,
+    pub first_player: Player, // who made the first move in this game
+    /// Players that resigned, they are skipped in the turn order
+    #[serde(default)]
+    pub eliminated: Vec<Player>,
+    #[serde(default)]
+    pub variants: Variants,
+    /// Moves played so far this game and who played them, in order
+    #[serde(default)]
+    pub moves: Vec<PlayedMove>,
+    /// Player asking to take back their last move, until another player answers
+    #[serde(default)]
+    pub takeback_request: Option<Player>,
+}
+
+impl GameData {
+    pub fn new(
+        width: u8,
+        height: u8,
+        win_length: u8,
+        game_id: IdType,
+        player_ids: Vec<IdType>,
+        first_player_rule: FirstPlayer,
+        variants: Variants,
+    ) -> Self {
+        let first_player = first_player_rule.pick(None, player_ids.len() as u8);
+        Self {
+            game_id,
+            board: Board::new(width, height),
+            win_length,
+            turn_player: first_player.clone(),
+            win_status: None,
+            winning_chips: None,
+            player_ids,
+            first_player_rule,
+            first_player,
+            eliminated: Vec::new(),
+            variants,
+            moves: Vec::new(),
+            takeback_request: None,
+        }
+    }
+
+    /// Fresh game between the same players for a rematch, the first player is picked again
+    pub fn new_round(&self) -> Self {
+        let first_player = self
+            .first_player_rule
+            .pick(Some(&self.first_player), self.num_players());
+        Self {
+            board: Board::new(self.board.width, self.board.height),
+            turn_player: first_player.clone(),
+            win_status: None,
+            winning_chips: None,
+            first_player,
+            eliminated: Vec::new(),
+            moves: Vec::new(),
+            takeback_request: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn num_players(&self) -> u8 {
+        self.player_ids.len() as u8
+    }
+
+    pub fn turn_player_id(&self) -> IdType {
+        self.player_ids[self.turn_player.index()]
+    }
+
+    /// The player seated with session id `id`, if any
+    pub fn seat_of(&self, id: IdType) -> Option<Player> {
+        let index = self
+            .player_ids
+            .iter()
+            .position(|player_id| *player_id == id)?;
+        Player::from_index(index)
+    }
+
+    // pub fn reset(&mut self) {
+    //     let _ = mem::replace(
+    //         self,
+    //         Self::new(self.board.width, self.board.height, self.win_length),
+    //     );
+    // }
+    // pub fn replace(&mut self, replacement: Self) {
+    //     let _ = mem::replace(self, replacement);
+    // }
+
+    // pub fn check_win(&self, col: usize, row: usize, player: &Player) -> Option<(i32, i32)> {
+    //     self.board.check_win(col, row, player, self.win_length)
+    // }
+
+    pub fn next_turn(&mut self) {
+        if let Some(next) =
+            rules::next_player(&self.turn_player, self.num_players(), &self.eliminated)
+        {
+            self.turn_player = next;
+        }
+    }
+
+    /// Takes `player` out of the game. If only one player is left, they win.
+    pub fn resign(&mut self, player: Player) {
+        if self.is_over() || self.eliminated.contains(&player) {
+            return;
+        }
+        self.eliminated.push(player.clone());
+        if self.turn_player == player {
+            self.next_turn();
+        }
+        self.win_status = rules::last_player_standing(self.num_players(), &self.eliminated);
+    }
+
+    /// Drops a chip for the turn player and passes the turn. Fails if the game is over or the column is full.
+    pub fn play_column(&mut self, column: usize) -> Result<(), InsertError> {
+        self.play_move(Move::Drop(column))
+    }
+
+    /// Plays `mv` for the turn player and passes the turn. Fails if the game is over or the move is not allowed.
+    pub fn play_move(&mut self, mv: Move) -> Result<(), InsertError> {
+        if self.win_status.is_some() {
+            return Err(InsertError);
+        }
+        let num_players = self.num_players();
+        let outcome = rules::play_move(
+            &mut self.board,
+            mv,
+            &self.turn_player,
+            self.win_length as usize,
+            num_players,
+            &self.eliminated,
+            &self.variants,
+        )?;
+        self.win_status = outcome.winner;
+        self.winning_chips = outcome.winning_chips;
+        self.moves.push(PlayedMove {
+            player: self.turn_player.clone(),
+            mv,
+            row: outcome.row,
+        });
+        self.takeback_request = None;
+        self.next_turn();
+        Ok(())
+    }
+
+    /// Whether the board filled up without a winner and the turn player can't move. Pop Out and roll
+    /// moves can still change a full board, so with those it is only a draw once the turn player
+    /// has no chip to pop and no row or column to roll.
+    pub fn is_draw(&self) -> bool {
+        self.win_status.is_none()
+            && self.board.is_full()
+            && !rules::has_legal_move(&self.board, &self.turn_player, &self.variants)
+    }
+
+    /// Whether someone won or the game ended in a draw
+    pub fn is_over(&self) -> bool {
+        self.win_status.is_some() || self.is_draw()
+    }
+
+    /// How the game went for each player, in seat order. None while the game is still going.
+    pub fn outcomes(&self) -> Option<Vec<GameOutcome>> {
+        let players = Player::in_game(self.num_players());
+        match &self.win_status {
+            Some(winner) => Some(
+                players
+                    .map(|player| {
+                        if player == *winner {
+                            GameOutcome::Won
+                        } else {
+                            GameOutcome::Lost
+                        }
+                    })
+                    .collect(),
+            ),
+            None if self.is_draw() => Some(players.map(|_| GameOutcome::Draw).collect()),
+            None => None,
+        }
+    }
+
+    /// Player that made the last move, if any move was made
+    pub fn last_mover(&self) -> Option<Player> {
+        self.moves.last().map(|played| played.player.clone())
+    }
+
+    /// Takes back the last move, the player that made it is on turn again
+    pub fn undo(&mut self) -> Option<Player> {
+        let played = self.moves.pop()?;
+        rules::undo_move(&mut self.board, &played).ok()?;
+        let player = played.player;
+        // no moves can be made after a line is completed, so only a resignation can have decided the game
+        self.win_status = rules::last_player_standing(self.num_players(), &self.eliminated);
+        self.winning_chips = None;
+        self.turn_player = player.clone();
+        Some(player)
+    }
+
+    /// Whether `player` may ask to take back the last move: they made it, nobody asked yet and the
+    /// game is still going, since the result of a finished game is already recorded
+    pub fn can_request_takeback(&self, player: &Player) -> bool {
+        self.takeback_request.is_none()
+            && !self.is_over()
+            && self.last_mover().as_ref() == Some(player)
+    }
+
+    /// Asks the other players to take back the last move, only the player that made it can ask
+    pub fn request_takeback(&mut self, player: Player) -> bool {
+        if !self.can_request_takeback(&player) {
+            return false;
+        }
+        self.takeback_request = Some(player);
+        true
+    }
+
+    /// Whether `player` may accept or decline the pending takeback request
+    pub fn can_answer_takeback(&self, player: &Player) -> bool {
+        match &self.takeback_request {
+            Some(requester) => requester != player && !self.eliminated.contains(player),
+            None => false,
+        }
+    }
+
+    /// Accepting undoes the last move, either way the request is cleared
+    pub fn answer_takeback(&mut self, player: &Player, accept: bool) -> bool {
+        if !self.can_answer_takeback(player) {
+            return false;
+        }
+        if accept {
+            self.undo();
+        }
+        self.takeback_request = None;
+        true
+    }
+
+    /// Takes over what `player` changed in `update`, their copy of this game: the moves they
+    /// played, their resignation, or a rematch once the game is over. The moves are replayed here,
+    /// so the rest of `update` is not trusted. Returns false and leaves the game as it was if
+    /// `update` has any other change.
+    pub fn apply_update(&mut self, update: &GameData, player: Player) -> bool {
+        if self.is_over() && update.moves.is_empty() && update.eliminated.is_empty() {
+            *self = self.new_round();
+            return true;
+        }
+        if !update.moves.starts_with(&self.moves)
+            || !update.eliminated.starts_with(&self.eliminated)
+        {
+            return false;
+        }
+        let new_moves = &update.moves[self.moves.len()..];
+        let resigned = &update.eliminated[self.eliminated.len()..];
+        if (new_moves.is_empty() && resigned.is_empty()) || resigned.iter().any(|p| *p != player) {
+            return false;
+        }
+        let mut game = self.clone();
+        for played in new_moves {
+            if played.player != player || game.turn_player != player {
+                return false;
+            }
+            if game.play_move(played.mv).is_err() {
+                return false;
+            }
+        }
+        if !resigned.is_empty() {
+            game.resign(player);
+        }
+        *self = game;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Full 4 by 4 board without a line of 4, in which player one has no chip in the bottom row
+    fn stuck_game(variants: Variants) -> GameData {
+        let mut game = GameData::new(4, 4, 4, 1, vec![1, 2, 3], FirstPlayer::Host, variants);
+        game.board = Board::from_rows(&["2111", "1333", "3121", "2322"]);
+        game
+    }
+
+    #[test]
+    fn full_board_is_a_draw() {
+        let game = stuck_game(Variants::default());
+        assert!(game.is_draw());
+        assert_eq!(game.outcomes(), Some(vec![GameOutcome::Draw; 3]));
+    }
+
+    #[test]
+    fn full_board_without_a_pop_is_a_draw() {
+        let pop_out = Variants {
+            pop_out: true,
+            ..Variants::default()
+        };
+        let mut game = stuck_game(pop_out);
+        assert_eq!(game.turn_player, Player::One);
+        assert!(game.is_draw());
+
+        // player two has a chip to pop, so the game goes on on their turn
+        game.turn_player = Player::Two;
+        assert!(!game.is_draw());
+        assert_eq!(game.outcomes(), None);
+    }
+
+    fn two_player_game() -> GameData {
+        GameData::new(
+            4,
+            4,
+            3,
+            1,
+            vec![1, 2],
+            FirstPlayer::Host,
+            Variants::default(),
+        )
+    }
+
+    #[test]
+    fn update_replays_the_new_moves() {
+        let mut game = two_player_game();
+        let mut update = game.clone();
+        update.play_column(0).unwrap();
+        // results the client claims are ignored, the replay decides them
+        update.win_status = Some(Player::One);
+        assert!(game.apply_update(&update, Player::One));
+        assert_eq!(game.moves, update.moves);
+        assert_eq!(game.board, update.board);
+        assert_eq!(game.turn_player, Player::Two);
+        assert_eq!(game.win_status, None);
+    }
+
+    #[test]
+    fn update_only_plays_the_senders_moves() {
+        let mut game = two_player_game();
+        let mut update = game.clone();
+        update.play_column(0).unwrap();
+        assert!(!game.apply_update(&update, Player::Two));
+
+        game.apply_update(&update, Player::One);
+        let mut takeback = game.clone();
+        takeback.undo();
+        assert!(!game.apply_update(&takeback, Player::One));
+        assert!(!game.apply_update(&takeback, Player::Two));
+        assert_eq!(game.moves.len(), 1);
+    }
+
+    #[test]
+    fn update_resigns_only_the_sender() {
+        let mut game = two_player_game();
+        let mut update = game.clone();
+        update.resign(Player::Two);
+        assert!(!game.apply_update(&update, Player::One));
+        assert!(game.apply_update(&update, Player::Two));
+        assert_eq!(game.win_status, Some(Player::One));
+    }
+
+    #[test]
+    fn rematch_only_once_the_game_is_over() {
+        let mut game = two_player_game();
+        game.play_column(0).unwrap();
+        let rematch = game.new_round();
+        assert!(!game.apply_update(&rematch, Player::Two));
+        assert_eq!(game.moves.len(), 1);
+
+        for column in [1, 0, 1, 0] {
+            game.play_column(column).unwrap();
+        }
+        assert_eq!(game.win_status, Some(Player::One));
+        assert!(game.apply_update(&rematch, Player::Two));
+        assert!(game.moves.is_empty());
+        assert_eq!(game.win_status, None);
+    }
+
+    #[test]
+    fn full_board_with_a_roll_left_is_not_a_draw() {
+        let roll_moves = Variants {
+            roll_moves: true,
+            ..Variants::default()
+        };
+        assert!(!stuck_game(roll_moves).is_draw());
+    }
+}
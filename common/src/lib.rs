@@ -0,0 +1,71 @@
+//! Game logic and api types shared by the frontend and the backend.
+//! Nothing in here may depend on yew or web-sys, so it compiles for both native and wasm targets.
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+pub mod board;
+pub mod chat;
+pub mod game;
+pub mod lobby;
+pub mod profile;
+pub mod rules;
+
+pub type IdType = u32;
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum Player {
+    One,
+    Two,
+    Three,
+    Four,
+}
+
+impl Player {
+    pub const MIN_PLAYERS: u8 = 2;
+    pub const MAX_PLAYERS: u8 = 4;
+    pub const ALL: [Player; 4] = [Player::One, Player::Two, Player::Three, Player::Four];
+
+    /// Seat of the player, starting at 0 for player one
+    pub fn index(&self) -> usize {
+        u8::from(self.clone()) as usize - 1
+    }
+
+    pub fn from_index(index: usize) -> Option<Player> {
+        Player::ALL.get(index).cloned()
+    }
+
+    /// The players in a game with `num_players` players
+    pub fn in_game(num_players: u8) -> impl Iterator<Item = Player> {
+        Player::ALL.into_iter().take(num_players as usize)
+    }
+}
+
+impl From<Player> for u8 {
+    fn from(player: Player) -> u8 {
+        match player {
+            Player::One => 1,
+            Player::Two => 2,
+            Player::Three => 3,
+            Player::Four => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for Player {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Player::One),
+            2 => Ok(Player::Two),
+            3 => Ok(Player::Three),
+            4 => Ok(Player::Four),
+            _ => Err("Invalid player identifier".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "p{}", u8::from(self.clone()))
+    }
+}
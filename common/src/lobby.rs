@@ -0,0 +1,446 @@
+use crate::rules::Variants;
+use crate::{IdType, Player};
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct GameLobby {
+    pub game_id: IdType,
+    /// Session ids of the seated players, one entry per seat. The host always has seat 0.
+    pub seats: Vec<Option<IdType>>,
+    pub game_name: String, // TODO: yew recommends using their AttrValue instead
+    // password: String
+    pub state: LobbyState,
+    pub settings: LobbySettings,
+}
+
+/// Settings chosen by the host, used by the backend to create the game once the lobby is full
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct LobbySettings {
+    pub width: u8,
+    pub height: u8,
+    pub win_length: u8,
+    pub num_players: u8,
+    pub first_player: FirstPlayer,
+    pub variants: Variants,
+    pub time_control: TimeControl,
+}
+
+impl Default for LobbySettings {
+    fn default() -> Self {
+        Self {
+            width: 7,
+            height: 6,
+            win_length: 4,
+            num_players: 2,
+            first_player: FirstPlayer::Host,
+            variants: Variants::default(),
+            time_control: TimeControl::default(),
+        }
+    }
+}
+
+/// Who gets the first move. The host is always player 1, the guests follow in the order they joined.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub enum FirstPlayer {
+    #[default]
+    Host,
+    Guest,
+    Random,
+    /// Host starts the first game, after that the next player in turn order starts each rematch
+    Alternate,
+}
+
+impl FirstPlayer {
+    pub const ALL: [FirstPlayer; 4] = [
+        FirstPlayer::Host,
+        FirstPlayer::Guest,
+        FirstPlayer::Random,
+        FirstPlayer::Alternate,
+    ];
+
+    /// Picks the starting player, `previous` is who started the previous game of a rematch series
+    pub fn pick(&self, previous: Option<&Player>, num_players: u8) -> Player {
+        let num_players = num_players.max(1) as usize;
+        let index = match (self, previous) {
+            (FirstPlayer::Host, _) | (FirstPlayer::Alternate, None) => 0,
+            (FirstPlayer::Guest, _) => 1,
+            (FirstPlayer::Random, _) => rand::random::<usize>() % num_players,
+            (FirstPlayer::Alternate, Some(previous)) => (previous.index() + 1) % num_players,
+        };
+        Player::from_index(index % num_players).unwrap_or(Player::One)
+    }
+}
+
+impl From<FirstPlayer> for u8 {
+    fn from(first_player: FirstPlayer) -> u8 {
+        match first_player {
+            FirstPlayer::Host => 0,
+            FirstPlayer::Guest => 1,
+            FirstPlayer::Random => 2,
+            FirstPlayer::Alternate => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for FirstPlayer {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FirstPlayer::Host),
+            1 => Ok(FirstPlayer::Guest),
+            2 => Ok(FirstPlayer::Random),
+            3 => Ok(FirstPlayer::Alternate),
+            _ => Err("Invalid first player identifier".to_owned()),
+        }
+    }
+}
+
+/// How long the host means to take per move, so players can find games at their pace
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub enum TimeControl {
+    #[default]
+    Unlimited,
+    /// Under a minute per move
+    Blitz,
+    /// A few minutes per move
+    Rapid,
+    /// Up to a day per move
+    Correspondence,
+}
+
+impl TimeControl {
+    pub const ALL: [TimeControl; 4] = [
+        TimeControl::Unlimited,
+        TimeControl::Blitz,
+        TimeControl::Rapid,
+        TimeControl::Correspondence,
+    ];
+}
+
+impl From<TimeControl> for u8 {
+    fn from(time_control: TimeControl) -> u8 {
+        match time_control {
+            TimeControl::Unlimited => 0,
+            TimeControl::Blitz => 1,
+            TimeControl::Rapid => 2,
+            TimeControl::Correspondence => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for TimeControl {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TimeControl::Unlimited),
+            1 => Ok(TimeControl::Blitz),
+            2 => Ok(TimeControl::Rapid),
+            3 => Ok(TimeControl::Correspondence),
+            _ => Err("Invalid time control identifier".to_owned()),
+        }
+    }
+}
+
+impl LobbySettings {
+    pub const MIN_SIZE: u8 = 3;
+    pub const MAX_SIZE: u8 = 15;
+    pub const MIN_WIN_LENGTH: u8 = 3;
+
+    pub fn is_valid(&self) -> bool {
+        let size_range = Self::MIN_SIZE..=Self::MAX_SIZE;
+        size_range.contains(&self.width)
+            && size_range.contains(&self.height)
+            && (Self::MIN_WIN_LENGTH..=self.width.max(self.height)).contains(&self.win_length)
+            && (Player::MIN_PLAYERS..=Player::MAX_PLAYERS).contains(&self.num_players)
+    }
+}
+
+/// Body of `/api/create_game_lobby`
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct NewLobby {
+    pub game_name: String,
+    pub settings: LobbySettings,
+}
+
+/// Lifecycle of a lobby: open -> full -> started -> finished/abandoned.
+/// A full lobby goes back to open when a guest leaves or is kicked.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum LobbyState {
+    Open,
+    Full,
+    Started,
+    Finished,
+    Abandoned,
+}
+
+impl LobbyState {
+    /// Whether players can still join, leave or be kicked
+    pub fn is_waiting(&self) -> bool {
+        matches!(self, LobbyState::Open | LobbyState::Full)
+    }
+
+    /// Whether the lobby is done with, either because the game ended or the lobby was closed
+    pub fn is_closed(&self) -> bool {
+        matches!(self, LobbyState::Finished | LobbyState::Abandoned)
+    }
+}
+
+impl From<LobbyState> for u8 {
+    fn from(state: LobbyState) -> u8 {
+        match state {
+            LobbyState::Open => 0,
+            LobbyState::Full => 1,
+            LobbyState::Started => 2,
+            LobbyState::Finished => 3,
+            LobbyState::Abandoned => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for LobbyState {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LobbyState::Open),
+            1 => Ok(LobbyState::Full),
+            2 => Ok(LobbyState::Started),
+            3 => Ok(LobbyState::Finished),
+            4 => Ok(LobbyState::Abandoned),
+            _ => Err("Invalid lobby state identifier".to_owned()),
+        }
+    }
+}
+
+/// Outcome of a request to join a lobby, as returned by `/api/join/<game_id>`.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum JoinResult {
+    Joined,
+    AlreadyJoined,
+    LobbyFull,
+    GameStarted,
+    LobbyClosed,
+    LobbyNotFound,
+    Failed,
+}
+
+impl JoinResult {
+    /// Whether the player has a seat in the lobby after the request
+    pub fn is_seated(&self) -> bool {
+        matches!(self, JoinResult::Joined | JoinResult::AlreadyJoined)
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LobbyAction {
+    Leave,
+    /// Removes the guest in the given seat
+    Kick(usize),
+    Cancel,
+}
+
+impl LobbyAction {
+    /// Path of the api route performing the action, relative to `/api`
+    pub fn route(&self, game_id: IdType) -> String {
+        match self {
+            LobbyAction::Leave => format!("leave/{}", game_id),
+            LobbyAction::Kick(seat) => format!("kick/{}/{}", game_id, seat),
+            LobbyAction::Cancel => format!("cancel/{}", game_id),
+        }
+    }
+}
+
+/// Outcome of a `LobbyAction`, as returned by `/api/<action>/<game_id>`
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum LobbyActionResult {
+    Done,
+    NotHost,
+    NotInLobby,
+    NoGuest,
+    GameStarted,
+    LobbyClosed,
+    LobbyNotFound,
+    Failed,
+}
+
+impl GameLobby {
+    pub fn number_players_joined(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.is_some()).count()
+    }
+
+    pub fn host_id(&self) -> Option<IdType> {
+        self.seats.first().copied().flatten()
+    }
+
+    /// Seat of the player with session id `id`, if they joined
+    pub fn seat_of(&self, id: IdType) -> Option<usize> {
+        self.seats.iter().position(|seat| *seat == Some(id))
+    }
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Debug)]
+pub struct GameList {
+    pub games: Vec<GameLobby>,
+}
+
+/// Order of the open games list
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub enum LobbySort {
+    #[default]
+    Newest,
+    Oldest,
+    HighestRating,
+    LowestRating,
+}
+
+impl LobbySort {
+    pub const ALL: [LobbySort; 4] = [
+        LobbySort::Newest,
+        LobbySort::Oldest,
+        LobbySort::HighestRating,
+        LobbySort::LowestRating,
+    ];
+}
+
+impl From<LobbySort> for u8 {
+    fn from(sort: LobbySort) -> u8 {
+        match sort {
+            LobbySort::Newest => 0,
+            LobbySort::Oldest => 1,
+            LobbySort::HighestRating => 2,
+            LobbySort::LowestRating => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for LobbySort {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(LobbySort::Newest),
+            1 => Ok(LobbySort::Oldest),
+            2 => Ok(LobbySort::HighestRating),
+            3 => Ok(LobbySort::LowestRating),
+            _ => Err("Invalid lobby sort identifier".to_owned()),
+        }
+    }
+}
+
+/// Filters and page of the open games list, sent to `/api/get_joinable_lobbies` as query
+/// parameters. Filters that are `None` or empty match every lobby.
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct LobbyFilter {
+    /// Part of the game name or of the host's display name
+    pub search: String,
+    pub width: Option<u8>,
+    pub height: Option<u8>,
+    pub win_length: Option<u8>,
+    pub time_control: Option<TimeControl>,
+    /// Range the host's rating has to be in
+    pub min_rating: Option<i32>,
+    pub max_rating: Option<i32>,
+    pub sort: LobbySort,
+    /// Starts at 0
+    pub page: u32,
+}
+
+impl LobbyFilter {
+    pub const PAGE_SIZE: u32 = 10;
+
+    /// The filter as url query parameters, without the leading '?'
+    pub fn query_string(&self) -> String {
+        let mut params = vec![
+            format!("sort={}", u8::from(self.sort)),
+            format!("page={}", self.page),
+        ];
+        if !self.search.is_empty() {
+            params.push(format!("search={}", percent_encode(&self.search)));
+        }
+        let optional = [
+            ("width", self.width.map(i32::from)),
+            ("height", self.height.map(i32::from)),
+            ("win_length", self.win_length.map(i32::from)),
+            (
+                "time_control",
+                self.time_control.map(|tc| i32::from(u8::from(tc))),
+            ),
+            ("min_rating", self.min_rating),
+            ("max_rating", self.max_rating),
+        ];
+        params.extend(
+            optional
+                .iter()
+                .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value))),
+        );
+        params.join("&")
+    }
+}
+
+/// Escapes everything but unreserved characters, so any text can be put in a query string
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// An open lobby in the list, with the rating of its host
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct OpenLobby {
+    pub lobby: GameLobby,
+    pub host_rating: i32,
+}
+
+/// One page of the open games a player can join
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct LobbyPage {
+    pub games: Vec<OpenLobby>,
+    /// Number of lobbies matching the filter, over all pages
+    pub matching: u32,
+    /// Number of open lobbies, filtered or not
+    pub open: u32,
+}
+
+impl LobbyPage {
+    pub fn num_pages(&self) -> u32 {
+        self.matching.div_ceil(LobbyFilter::PAGE_SIZE).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_first_players() {
+        assert_eq!(FirstPlayer::Host.pick(Some(&Player::Two), 2), Player::One);
+        assert_eq!(FirstPlayer::Guest.pick(None, 3), Player::Two);
+    }
+
+    #[test]
+    fn alternate_passes_the_first_move_around() {
+        assert_eq!(FirstPlayer::Alternate.pick(None, 3), Player::One);
+        assert_eq!(
+            FirstPlayer::Alternate.pick(Some(&Player::Two), 3),
+            Player::Three
+        );
+        assert_eq!(
+            FirstPlayer::Alternate.pick(Some(&Player::Three), 3),
+            Player::One
+        );
+        assert_eq!(
+            FirstPlayer::Alternate.pick(Some(&Player::Two), 2),
+            Player::One
+        );
+    }
+
+    #[test]
+    fn random_picks_a_seated_player() {
+        for _ in 0..100 {
+            assert!(FirstPlayer::Random.pick(None, 3).index() < 3);
+        }
+    }
+}
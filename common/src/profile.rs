@@ -0,0 +1,162 @@
+use crate::IdType;
+use serde::{Deserialize, Serialize};
+
+/// Rating of a player that has not finished a game yet
+pub const START_RATING: i32 = 1200;
+/// Most a rating can change in a two player game
+const K_FACTOR: f64 = 32.;
+
+/// What other players see of a player: their name and avatar colour
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct PlayerInfo {
+    pub player_id: IdType,
+    /// None until the player picks a name on their profile
+    pub display_name: Option<String>,
+    /// Css colour like "#1e90ff"
+    pub avatar_color: Option<String>,
+}
+
+impl PlayerInfo {
+    /// Info of a player that never saved their profile
+    pub fn anonymous(player_id: IdType) -> Self {
+        Self {
+            player_id,
+            display_name: None,
+            avatar_color: None,
+        }
+    }
+}
+
+/// Sent by a player to change their own name and avatar colour
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct ProfileUpdate {
+    pub display_name: String,
+    pub avatar_color: Option<String>,
+}
+
+impl ProfileUpdate {
+    pub const MAX_NAME_LENGTH: usize = 24;
+
+    pub fn is_valid(&self) -> bool {
+        let name_length = self.display_name.chars().count();
+        let name_valid = (1..=Self::MAX_NAME_LENGTH).contains(&name_length)
+            && self.display_name.trim() == self.display_name
+            && !self.display_name.chars().any(char::is_control);
+        name_valid && self.avatar_color.as_deref().is_none_or(is_hex_color)
+    }
+}
+
+/// Whether `color` is a colour in the "#rrggbb" form colour inputs give, so it is safe to put in a style
+pub fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// How a finished game went for one of its players
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+pub enum GameOutcome {
+    Won,
+    Lost,
+    Draw,
+}
+
+impl From<GameOutcome> for u8 {
+    fn from(outcome: GameOutcome) -> u8 {
+        match outcome {
+            GameOutcome::Won => 0,
+            GameOutcome::Lost => 1,
+            GameOutcome::Draw => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for GameOutcome {
+    type Error = String;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(GameOutcome::Won),
+            1 => Ok(GameOutcome::Lost),
+            2 => Ok(GameOutcome::Draw),
+            _ => Err("Invalid game outcome identifier".to_owned()),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+/// A finished game in a player's history
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct FinishedGame {
+    pub game_id: IdType,
+    pub game_name: String,
+    pub outcome: GameOutcome,
+    pub rating_change: i32,
+    pub opponents: Vec<PlayerInfo>,
+    /// Date the game ended, as "YYYY-MM-DD"
+    pub finished_on: String,
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct Profile {
+    pub info: PlayerInfo,
+    pub rating: i32,
+    pub record: Record,
+    /// Most recent games first
+    pub history: Vec<FinishedGame>,
+}
+
+/// Elo rating changes for the players of a finished game. Every pair of players counts as a match,
+/// the winner beats everyone and the other players draw among themselves. The changes are scaled
+/// down by the number of opponents, so a game is worth as much with four players as with two.
+pub fn rating_changes(ratings: &[i32], outcomes: &[GameOutcome]) -> Vec<i32> {
+    let opponents = ratings.len().saturating_sub(1).max(1) as f64;
+    let score = |own: GameOutcome, other: GameOutcome| match (own, other) {
+        (GameOutcome::Won, _) => 1.,
+        (_, GameOutcome::Won) => 0.,
+        _ => 0.5,
+    };
+    (0..ratings.len())
+        .map(|i| {
+            let change: f64 = (0..ratings.len())
+                .filter(|&j| j != i)
+                .map(|j| {
+                    let expected = 1. / (1. + 10f64.powf((ratings[j] - ratings[i]) as f64 / 400.));
+                    score(outcomes[i], outcomes[j]) - expected
+                })
+                .sum();
+            (K_FACTOR * change / opponents).round() as i32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GameOutcome::*;
+
+    #[test]
+    fn even_game_moves_half_the_k_factor() {
+        assert_eq!(rating_changes(&[1200, 1200], &[Won, Lost]), vec![16, -16]);
+        assert_eq!(rating_changes(&[1200, 1200], &[Draw, Draw]), vec![0, 0]);
+    }
+
+    #[test]
+    fn beating_a_weaker_player_is_worth_less() {
+        let changes = rating_changes(&[1400, 1200], &[Won, Lost]);
+        assert!(changes[0] > 0 && changes[0] < 16);
+        assert_eq!(changes[0], -changes[1]);
+        // the weaker player gains from a draw
+        let changes = rating_changes(&[1400, 1200], &[Draw, Draw]);
+        assert!(changes[0] < 0 && changes[1] > 0);
+    }
+
+    #[test]
+    fn multiplayer_games_are_scaled_by_the_number_of_opponents() {
+        let changes = rating_changes(&[1200; 4], &[Lost, Won, Lost, Lost]);
+        assert_eq!(changes, vec![-5, 16, -5, -5]);
+    }
+}
@@ -0,0 +1,538 @@
+//! Rules of connect 4 on a torus, shared by local and online games
+
+use crate::board::{Board, InsertError};
+use crate::Player;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Optional rules on top of plain connect 4 on a torus, picked in the lobby settings
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Variants {
+    /// Players may pop their own chip from the bottom of a column, see `pop_chip`
+    #[serde(default)]
+    pub pop_out: bool,
+    /// Players may roll a row or column instead of dropping a chip, see `roll`
+    #[serde(default)]
+    pub roll_moves: bool,
+    /// Chips can also be pushed in from the bottom edge, rising until they hit a chip. The chips
+    /// dropped from the top and the ones pushed in from the bottom meet across the wrapped edge.
+    #[serde(default)]
+    pub wrap_gravity: bool,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Move {
+    /// Drops a chip on top of the column
+    Drop(usize),
+    /// Wrap gravity: pushes a chip in from the bottom of the column
+    DropFromBottom(usize),
+    /// Pop Out: removes the player's own chip from the bottom of the column
+    Pop(usize),
+    /// Shifts a column one cell up or down, the chip falling off one end comes back at the other
+    RollColumn { column: usize, up: bool },
+    /// Shifts a row one cell to the right or left, wrapping around
+    RollRow { row: usize, right: bool },
+}
+
+impl Move {
+    pub fn is_allowed_by(&self, variants: &Variants) -> bool {
+        match self {
+            Move::Drop(_) => true,
+            Move::DropFromBottom(_) => variants.wrap_gravity,
+            Move::Pop(_) => variants.pop_out,
+            Move::RollColumn { .. } | Move::RollRow { .. } => variants.roll_moves,
+        }
+    }
+}
+
+pub struct DropOutcome {
+    /// Row the chip landed in
+    pub row: usize,
+    /// Set if the move completed a line
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+}
+
+/// Drops a chip for `player` in `column` and checks if it completes a line of `win_length`
+pub fn drop_chip(
+    board: &mut Board,
+    column: usize,
+    from_bottom: bool,
+    player: &Player,
+    win_length: usize,
+) -> Result<DropOutcome, InsertError> {
+    let row = match from_bottom {
+        true => board.insert_from_bottom(column, player)?,
+        false => board.insert(column, player)?,
+    };
+    let winning_chips = match board.check_win(column, row, player, win_length) {
+        true => Some(board.find_winning_chips(column, row, player, win_length)),
+        false => None,
+    };
+    Ok(DropOutcome { row, winning_chips })
+}
+
+/// Result of a move that can complete lines for several players at once
+pub struct MoveOutcome {
+    /// Set if the move completed a line, see `find_winner` for who wins if it completed several
+    pub winner: Option<Player>,
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    /// Row the chip landed in, for drops
+    pub row: Option<usize>,
+}
+
+/// A move in the history of a game, with what is needed to take it back
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PlayedMove {
+    pub player: Player,
+    pub mv: Move,
+    /// Row the chip landed in, for drops. With wrap gravity this can not be told from the board.
+    pub row: Option<usize>,
+}
+
+/// Plays `mv` for `player` with the rules in `variants`. Fails if the move is not allowed.
+pub fn play_move(
+    board: &mut Board,
+    mv: Move,
+    player: &Player,
+    win_length: usize,
+    num_players: u8,
+    eliminated: &[Player],
+    variants: &Variants,
+) -> Result<MoveOutcome, InsertError> {
+    if !mv.is_allowed_by(variants) {
+        return Err(InsertError);
+    }
+    match mv {
+        Move::Drop(column) | Move::DropFromBottom(column) => {
+            let from_bottom = matches!(mv, Move::DropFromBottom(_));
+            let outcome = drop_chip(board, column, from_bottom, player, win_length)?;
+            Ok(MoveOutcome {
+                winner: outcome.winning_chips.as_ref().map(|_| player.clone()),
+                winning_chips: outcome.winning_chips,
+                row: Some(outcome.row),
+            })
+        }
+        Move::Pop(column) => pop_chip(board, column, player, win_length, num_players, eliminated),
+        Move::RollColumn { .. } | Move::RollRow { .. } => roll(
+            board,
+            mv,
+            player,
+            win_length,
+            num_players,
+            eliminated,
+            variants.wrap_gravity,
+        ),
+    }
+}
+
+/// Checks the lines through all chips in `cells` after they moved. The player that moved wins if
+/// they have a line, otherwise the first player after them in turn order that has one.
+fn find_winner(
+    board: &Board,
+    cells: &[(usize, usize)],
+    mover: &Player,
+    win_length: usize,
+    num_players: u8,
+    eliminated: &[Player],
+) -> MoveOutcome {
+    let turn_order = (0..num_players as usize)
+        .filter_map(|offset| Player::from_index((mover.index() + offset) % num_players as usize))
+        .filter(|candidate| !eliminated.contains(candidate));
+    for candidate in turn_order {
+        let winning_chips: HashSet<(usize, usize)> = cells
+            .iter()
+            .filter(|(col, row)| {
+                board.board[*col][*row].as_ref() == Some(&candidate)
+                    && board.check_win(*col, *row, &candidate, win_length)
+            })
+            .flat_map(|(col, row)| board.find_winning_chips(*col, *row, &candidate, win_length))
+            .collect();
+        if !winning_chips.is_empty() {
+            return MoveOutcome {
+                winner: Some(candidate),
+                winning_chips: Some(winning_chips),
+                row: None,
+            };
+        }
+    }
+    MoveOutcome {
+        winner: None,
+        winning_chips: None,
+        row: None,
+    }
+}
+
+/// Whether `player` may pop the bottom chip of `column`, which has to be one of their own
+pub fn can_pop(board: &Board, column: usize, player: &Player) -> bool {
+    board
+        .board
+        .get(column)
+        .and_then(|cells| cells.first())
+        .is_some_and(|bottom| bottom.as_ref() == Some(player))
+}
+
+/// Pops `player`'s chip from the bottom of `column`. All chips in the column move, so they can
+/// complete lines for several players at once.
+pub fn pop_chip(
+    board: &mut Board,
+    column: usize,
+    player: &Player,
+    win_length: usize,
+    num_players: u8,
+    eliminated: &[Player],
+) -> Result<MoveOutcome, InsertError> {
+    if !can_pop(board, column, player) {
+        return Err(InsertError);
+    }
+    board.pop_bottom(column);
+    let cells: Vec<(usize, usize)> = (0..board.height as usize)
+        .map(|row| (column, row))
+        .collect();
+    Ok(find_winner(
+        board,
+        &cells,
+        player,
+        win_length,
+        num_players,
+        eliminated,
+    ))
+}
+
+/// Rolls a column or row by one cell, wrapping around the torus. The roll is only allowed if it
+/// changes the board and no chip ends up floating, see `Board::is_settled`. Without wrap gravity
+/// this means a column can only be rolled when it is full. Every line through the rolled cells is
+/// checked for a win.
+pub fn roll(
+    board: &mut Board,
+    mv: Move,
+    player: &Player,
+    win_length: usize,
+    num_players: u8,
+    eliminated: &[Player],
+    wrap_gravity: bool,
+) -> Result<MoveOutcome, InsertError> {
+    let cells = roll_cells(board, mv, wrap_gravity)?;
+    Ok(find_winner(
+        board,
+        &cells,
+        player,
+        win_length,
+        num_players,
+        eliminated,
+    ))
+}
+
+/// Rolls the board for `mv` and returns the cells that moved, see `roll` for when that is allowed.
+/// The board is left as it was if it isn't.
+fn roll_cells(
+    board: &mut Board,
+    mv: Move,
+    wrap_gravity: bool,
+) -> Result<Vec<(usize, usize)>, InsertError> {
+    let before = board.clone();
+    let cells: Vec<(usize, usize)> = match mv {
+        Move::RollColumn { column, up } => {
+            board.roll_column(column, up)?;
+            (0..board.height as usize)
+                .map(|row| (column, row))
+                .collect()
+        }
+        Move::RollRow { row, right } => {
+            board.roll_row(row, right)?;
+            (0..board.width as usize).map(|col| (col, row)).collect()
+        }
+        Move::Drop(_) | Move::DropFromBottom(_) | Move::Pop(_) => return Err(InsertError),
+    };
+    if *board == before || !board.is_settled(wrap_gravity) {
+        *board = before;
+        return Err(InsertError);
+    }
+    Ok(cells)
+}
+
+/// Whether `player` may play `mv` on `board`, the board itself is not changed
+pub fn is_legal(board: &Board, mv: Move, player: &Player, variants: &Variants) -> bool {
+    if !mv.is_allowed_by(variants) {
+        return false;
+    }
+    let mut board = board.clone();
+    match mv {
+        Move::Drop(column) => board.insert(column, player).is_ok(),
+        Move::DropFromBottom(column) => board.insert_from_bottom(column, player).is_ok(),
+        Move::Pop(column) => can_pop(&board, column, player),
+        Move::RollColumn { .. } | Move::RollRow { .. } => {
+            roll_cells(&mut board, mv, variants.wrap_gravity).is_ok()
+        }
+    }
+}
+
+/// Whether `player` can make any move on `board` with the rules in `variants`
+pub fn has_legal_move(board: &Board, player: &Player, variants: &Variants) -> bool {
+    let column_moves = (0..board.width as usize).flat_map(|column| {
+        [
+            Move::Drop(column),
+            Move::DropFromBottom(column),
+            Move::Pop(column),
+            Move::RollColumn { column, up: true },
+            Move::RollColumn { column, up: false },
+        ]
+    });
+    let row_moves = (0..board.height as usize).flat_map(|row| {
+        [
+            Move::RollRow { row, right: true },
+            Move::RollRow { row, right: false },
+        ]
+    });
+    column_moves
+        .chain(row_moves)
+        .any(|mv| is_legal(board, mv, player, variants))
+}
+
+/// Reverts a move on the board
+pub fn undo_move(board: &mut Board, played: &PlayedMove) -> Result<(), InsertError> {
+    match (played.mv, played.row) {
+        (Move::Drop(column) | Move::DropFromBottom(column), Some(row)) => board
+            .board
+            .get_mut(column)
+            .and_then(|cells| cells.get_mut(row))
+            .and_then(Option::take)
+            .map(|_| ())
+            .ok_or(InsertError),
+        (Move::Drop(_) | Move::DropFromBottom(_), None) => Err(InsertError),
+        (Move::Pop(column), _) => board.push_bottom(column, &played.player),
+        (Move::RollColumn { column, up }, _) => board.roll_column(column, !up),
+        (Move::RollRow { row, right }, _) => board.roll_row(row, !right),
+    }
+}
+
+/// Player whose turn is after `player`, skipping `eliminated` players. `None` if nobody else is left.
+pub fn next_player(player: &Player, num_players: u8, eliminated: &[Player]) -> Option<Player> {
+    let num_players = num_players as usize;
+    (1..num_players)
+        .filter_map(|offset| Player::from_index((player.index() + offset) % num_players))
+        .find(|next| !eliminated.contains(next))
+}
+
+/// The only player left when everyone else is eliminated, they win the game
+pub fn last_player_standing(num_players: u8, eliminated: &[Player]) -> Option<Player> {
+    let mut remaining = Player::in_game(num_players).filter(|player| !eliminated.contains(player));
+    match (remaining.next(), remaining.next()) {
+        (Some(player), None) => Some(player),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_VARIANTS: Variants = Variants {
+        pop_out: true,
+        roll_moves: true,
+        wrap_gravity: true,
+    };
+
+    fn play(
+        board: &mut Board,
+        mv: Move,
+        player: &Player,
+        variants: &Variants,
+    ) -> Result<MoveOutcome, InsertError> {
+        play_move(board, mv, player, 3, 2, &[], variants)
+    }
+
+    #[test]
+    fn moves_outside_the_board_fail() {
+        let mut board = Board::new(3, 3);
+        for mv in [
+            Move::Drop(3),
+            Move::DropFromBottom(3),
+            Move::Pop(3),
+            Move::RollColumn {
+                column: 3,
+                up: true,
+            },
+            Move::RollRow {
+                row: 3,
+                right: true,
+            },
+        ] {
+            assert!(play(&mut board, mv, &Player::One, &ALL_VARIANTS).is_err());
+        }
+        assert_eq!(board, Board::new(3, 3));
+    }
+
+    #[test]
+    fn moves_need_their_variant() {
+        let mut board = Board::from_rows(&["1", "2", "1"]);
+        let plain = Variants::default();
+        assert!(play(&mut board, Move::Pop(0), &Player::One, &plain).is_err());
+        assert!(play(
+            &mut board,
+            Move::RollColumn {
+                column: 0,
+                up: true
+            },
+            &Player::One,
+            &plain
+        )
+        .is_err());
+        assert!(play(
+            &mut Board::new(1, 3),
+            Move::DropFromBottom(0),
+            &Player::One,
+            &plain
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn pop_own_bottom_chip_and_undo_it() {
+        let before = Board::from_rows(&["..", "2.", "12"]);
+        let mut board = before.clone();
+        assert!(play(&mut board, Move::Pop(1), &Player::One, &ALL_VARIANTS).is_err());
+        let outcome = play(&mut board, Move::Pop(0), &Player::One, &ALL_VARIANTS).unwrap();
+        assert_eq!(outcome.winner, None);
+        assert_eq!(board, Board::from_rows(&["..", "..", "22"]));
+
+        let played = PlayedMove {
+            player: Player::One,
+            mv: Move::Pop(0),
+            row: outcome.row,
+        };
+        undo_move(&mut board, &played).unwrap();
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn rolls_must_leave_the_board_settled() {
+        let before = Board::from_rows(&["..", "2.", "12"]);
+        let gravity = Variants {
+            roll_moves: true,
+            ..Variants::default()
+        };
+        let mut board = before.clone();
+        let roll_up = Move::RollColumn {
+            column: 0,
+            up: true,
+        };
+        // the column isn't full, so rolling it would leave a chip floating
+        assert!(play(&mut board, roll_up, &Player::One, &gravity).is_err());
+        assert_eq!(board, before);
+        // with wrap gravity the chips can hang from the top instead
+        play(&mut board, roll_up, &Player::One, &ALL_VARIANTS).unwrap();
+        assert_eq!(board, Board::from_rows(&["2.", "1.", ".2"]));
+
+        let mut full = Board::from_rows(&["1", "2", "2"]);
+        play(
+            &mut full,
+            Move::RollColumn {
+                column: 0,
+                up: false,
+            },
+            &Player::One,
+            &gravity,
+        )
+        .unwrap();
+        assert_eq!(full, Board::from_rows(&["2", "1", "2"]));
+    }
+
+    #[test]
+    fn rolls_must_change_the_board() {
+        let mut board = Board::from_rows(&["..", "11"]);
+        let roll_row = Move::RollRow {
+            row: 0,
+            right: true,
+        };
+        assert!(play(&mut board, roll_row, &Player::One, &ALL_VARIANTS).is_err());
+        assert!(!is_legal(&board, roll_row, &Player::One, &ALL_VARIANTS));
+    }
+
+    #[test]
+    fn undo_takes_back_drops_and_rolls() {
+        let mut board = Board::new(3, 3);
+        let moves = [
+            Move::Drop(0),
+            Move::DropFromBottom(0),
+            Move::RollRow {
+                row: 0,
+                right: false,
+            },
+        ];
+        let mut played = Vec::new();
+        let mut boards = vec![board.clone()];
+        for mv in moves {
+            let outcome = play(&mut board, mv, &Player::Two, &ALL_VARIANTS).unwrap();
+            played.push(PlayedMove {
+                player: Player::Two,
+                mv,
+                row: outcome.row,
+            });
+            boards.push(board.clone());
+        }
+        assert_eq!(played[1].row, Some(2));
+        for played in played.iter().rev() {
+            boards.pop();
+            undo_move(&mut board, played).unwrap();
+            assert_eq!(&board, boards.last().unwrap());
+        }
+    }
+
+    #[test]
+    fn mover_wins_when_a_move_completes_several_lines() {
+        // popping player one's chip completes a row for both players
+        let mut board = Board::from_rows(&["1..", "211", "122"]);
+        let outcome = pop_chip(&mut board, 0, &Player::One, 3, 2, &[]).unwrap();
+        assert_eq!(outcome.winner, Some(Player::One));
+        let expected: HashSet<_> = [(0, 1), (1, 1), (2, 1)].into_iter().collect();
+        assert_eq!(outcome.winning_chips, Some(expected));
+    }
+
+    #[test]
+    fn next_player_in_turn_order_wins_lines_completed_for_others() {
+        // popping player one's chip completes a row for players two and three
+        let before = Board::from_rows(&["3..", "233", "122"]);
+        let mut board = before.clone();
+        let outcome = pop_chip(&mut board, 0, &Player::One, 3, 3, &[]).unwrap();
+        assert_eq!(outcome.winner, Some(Player::Two));
+
+        let mut board = before.clone();
+        let outcome = pop_chip(&mut board, 0, &Player::Three, 3, 3, &[]);
+        assert!(outcome.is_err(), "player three can't pop player one's chip");
+
+        // a player that resigned can't win, the next one in turn order does
+        let mut board = before;
+        let outcome = pop_chip(&mut board, 0, &Player::One, 3, 3, &[Player::Two]).unwrap();
+        assert_eq!(outcome.winner, Some(Player::Three));
+    }
+
+    #[test]
+    fn legal_moves_on_a_full_board() {
+        let board = Board::from_rows(&["12", "21"]);
+        assert!(!has_legal_move(&board, &Player::One, &Variants::default()));
+        let pop_out = Variants {
+            pop_out: true,
+            ..Variants::default()
+        };
+        assert!(has_legal_move(&board, &Player::One, &pop_out));
+        assert!(!has_legal_move(&board, &Player::Three, &pop_out));
+    }
+
+    #[test]
+    fn turn_order_skips_eliminated_players() {
+        assert_eq!(next_player(&Player::Two, 3, &[]), Some(Player::Three));
+        assert_eq!(
+            next_player(&Player::Two, 3, &[Player::Three]),
+            Some(Player::One)
+        );
+        assert_eq!(
+            next_player(&Player::Two, 3, &[Player::One, Player::Three]),
+            None
+        );
+        assert_eq!(last_player_standing(3, &[Player::One]), None);
+        assert_eq!(
+            last_player_standing(3, &[Player::One, Player::Three]),
+            Some(Player::Two)
+        );
+    }
+}
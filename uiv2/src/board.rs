@@ -1,192 +1,418 @@
-use crate::cell::Cell;
-use crate::Player;
-use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use crate::cell::{Cell, MoveMarker};
+use crate::i18n::{t, tn};
+use crate::settings::Settings;
+use crate::tiledboard::TiledBoardView;
+use crate::torusview::TorusView;
+use common::board::Board;
+use common::rules::{self, Move, PlayedMove, Variants};
+use common::Player;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, EventTarget, HtmlElement, HtmlSelectElement, Node};
 use yew::prelude::*;
 
-fn modulo(n: i32, m: i32) -> i32 {
-    // always returns in range [0,m)
-    (n % m + m) % m
+/// Ways to draw the board, picked by the player
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BoardViewMode {
+    /// The board once, with buttons for every kind of move
+    Flat,
+    /// The board tiled 3x3 times and pannable, see `TiledBoardView`
+    Tiled,
+    /// The board on a 3d torus that can be turned, see `TorusView`
+    Torus,
 }
 
-#[derive(Debug, Clone)]
-pub struct InsertError;
+impl BoardViewMode {
+    pub const ALL: [BoardViewMode; 3] = [
+        BoardViewMode::Flat,
+        BoardViewMode::Tiled,
+        BoardViewMode::Torus,
+    ];
 
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Properties)]
-pub struct Board {
-    pub board: Vec<Vec<Option<Player>>>,
-    pub width: u8,
-    pub height: u8,
-}
+    fn label(&self) -> String {
+        t(match self {
+            BoardViewMode::Flat => "board.flat",
+            BoardViewMode::Tiled => "board.tiled",
+            BoardViewMode::Torus => "board.torus",
+        })
+    }
 
-impl Board {
-    pub fn new(width: u8, height: u8) -> Self {
-        let mut empty_col = Vec::new();
-        for _ in 0..height {
-            empty_col.push(None);
-        }
-        let mut board = Vec::new();
-        for _ in 0..width {
-            board.push(empty_col.clone());
-        }
-        Board {
-            board,
-            width,
-            height,
-        }
+    /// Whether every move of a game with `variants` can be played from this view. The tiled and
+    /// torus views only take drops.
+    fn plays(&self, variants: &Variants) -> bool {
+        *self == BoardViewMode::Flat
+            || !(variants.pop_out || variants.roll_moves || variants.wrap_gravity)
     }
+}
 
-    pub fn insert(&mut self, column: usize, player: &Player) -> Result<usize, InsertError> {
-        for row in 0..self.height {
-            if self.board[column][row as usize].is_none() {
-                self.board[column][row as usize] = Some(player.clone());
-                return Ok(row.into());
-            }
-        }
-        Err(InsertError)
+#[derive(PartialEq, Properties)]
+pub struct BoardProps {
+    pub board: Board,
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    pub column_callbacks: Vec<Callback<MouseEvent>>, // define in gamedata component as  ctx.link().callback(move |_| Msg::ColumnClick(colnr))
+    /// Pop Out games get a pop button below each column
+    #[prop_or_default]
+    pub pop_callbacks: Option<Vec<Callback<MouseEvent>>>,
+    /// Wrap gravity games get a push button below each column to insert a chip from the bottom
+    #[prop_or_default]
+    pub push_callbacks: Option<Vec<Callback<MouseEvent>>>,
+    /// Games with roll moves get arrows to roll each column and row
+    #[prop_or_default]
+    pub on_roll: Option<Callback<Move>>,
+    /// Player whose chip is previewed for the drop or push the mouse or focus is on, none if the
+    /// viewer can not move
+    #[prop_or_default]
+    pub turn_player: Option<Player>,
+    /// Length of a winning line, for highlighting the lines a previewed chip would complete
+    pub win_length: usize,
+    /// Rules of the game, for the threats a player's next move could make
+    pub variants: Variants,
+    /// Moves played so far, the last ones are marked on the board and the last dropped chip is
+    /// animated falling into place
+    #[prop_or_default]
+    pub moves: Vec<PlayedMove>,
+}
+
+/// Choices for how many of the last moves are marked on the board
+const MARKED_MOVES_CHOICES: [usize; 4] = [0, 1, 3, 5];
+
+/// Cell a move put a chip in, if it was a single cell
+fn played_cell(played: &PlayedMove) -> Option<(usize, usize)> {
+    match played.mv {
+        Move::Drop(column) | Move::DropFromBottom(column) => Some((column, played.row?)),
+        Move::Pop(_) | Move::RollColumn { .. } | Move::RollRow { .. } => None,
     }
+}
 
-    #[allow(dead_code)] //TODO: remove function if not necessary
-    fn column_full(&self, colnr: usize) -> bool {
-        self.board[colnr].last().is_some()
+/// Column a chip was popped out of. The chips above it moved down, so the column is marked
+/// instead of a cell.
+fn popped_column(played: &PlayedMove) -> Option<usize> {
+    match played.mv {
+        Move::Pop(column) => Some(column),
+        _ => None,
     }
+}
+
+/// Marks for the last `count` moves at the spot `marked` picks for each, the newest mark wins if
+/// a spot was played twice
+fn move_markers<T: Eq + Hash>(
+    moves: &[PlayedMove],
+    count: usize,
+    marked: fn(&PlayedMove) -> Option<T>,
+) -> HashMap<T, MoveMarker> {
+    moves
+        .iter()
+        .rev()
+        .take(count)
+        .enumerate()
+        .rev()
+        .filter_map(|(age, played)| {
+            let marker = match count {
+                1 => MoveMarker::Dot,
+                _ => MoveMarker::Age(age + 1),
+            };
+            Some((marked(played)?, marker))
+        })
+        .collect()
+}
+
+/// Arrow below a column a chip was popped out of recently, numbered like the cell markers
+fn pop_marker(marker: MoveMarker) -> Html {
+    let text = match marker {
+        MoveMarker::Dot => "↓".to_owned(),
+        MoveMarker::Age(age) => format!("↓{}", age),
+    };
+    html! { <div class="popmarker" title={t("board.popped")}>{text}</div> }
+}
+
+/// Cells where a player would complete a line with their next drop, see `Board::threats`. Only
+/// players with chips on the board are checked.
+fn threat_cells(boardprops: &BoardProps) -> HashMap<(usize, usize), Player> {
+    let board = &boardprops.board;
+    // bottom drops are only possible with wrap gravity
+    let wrap_gravity = boardprops.variants.wrap_gravity;
+    Player::ALL
+        .iter()
+        .filter(|player| {
+            board
+                .board
+                .iter()
+                .flatten()
+                .any(|cell| cell.as_ref() == Some(*player))
+        })
+        .flat_map(|player| {
+            board
+                .threats(player, boardprops.win_length, wrap_gravity)
+                .into_iter()
+                .map(move |cell| (cell, player.clone()))
+        })
+        .collect()
+}
 
-    fn shift_coords(
-        &self,
-        (col, row): (usize, usize),
-        (dx, dy): (i32, i32),
-        amount: i32,
-    ) -> (usize, usize) {
-        let pre_x = modulo(col as i32 + amount * dx, self.width as i32);
-        let pre_y = modulo(row as i32 + amount * dy, self.height as i32);
-        // log::info!("{}", format!("{} {}", pre_x, pre_y));
-        let x: usize = pre_x.try_into().unwrap();
-        let y: usize = pre_y.try_into().unwrap();
-        (x, y)
+/// Cell the last move dropped a chip in and how many cells it falls to get there, see `cell_svg`
+fn dropped_chip(boardprops: &BoardProps) -> Option<((usize, usize), i32)> {
+    let played = boardprops.moves.last()?;
+    let row = played.row?;
+    match played.mv {
+        Move::Drop(column) => Some(((column, row), boardprops.board.height as i32 - row as i32)),
+        Move::DropFromBottom(column) => Some(((column, row), -(row as i32) - 1)),
+        _ => None,
     }
+}
 
-    fn check_line(
-        &self,
-        col: usize,
-        row: usize,
-        dx: i32,
-        dy: i32,
-        player: &Player,
-        win_length: usize,
-    ) -> bool {
-        // Note that this does not check for duplicates in the same line
-        // i.e. if the board is smaller than the win_length in some direction
-        // filling up that direction with one color will be a win even though it's shorter than win_length
-        let mut consecutive = 0;
-        for s in -(win_length as i32)..(win_length as i32) {
-            let (x, y) = self.shift_coords((col, row), (dx, dy), s as i32);
-            let cell_status = self.board[x][y].clone(); // why clone?
-            if cell_status == Some(player.clone()) {
-                consecutive += 1;
-            } else {
-                consecutive = 0;
-            }
+/// Where the chip of the hovered drop or push would land, and the lines it would complete
+struct Preview {
+    player: Player,
+    cell: (usize, usize),
+    lines: HashSet<(usize, usize)>,
+}
 
-            if consecutive >= win_length {
-                return true;
-            }
-        }
-        false
+impl Preview {
+    /// None for moves that don't put a chip in a single cell
+    fn new(boardprops: &BoardProps, mv: Move, show_lines: bool) -> Option<Preview> {
+        let (column, from_bottom) = match mv {
+            Move::Drop(column) => (column, false),
+            Move::DropFromBottom(column) => (column, true),
+            Move::Pop(_) | Move::RollColumn { .. } | Move::RollRow { .. } => return None,
+        };
+        let player = boardprops.turn_player.clone()?;
+        let mut board = boardprops.board.clone();
+        let outcome = rules::drop_chip(
+            &mut board,
+            column,
+            from_bottom,
+            &player,
+            boardprops.win_length,
+        )
+        .ok()?;
+        let lines = match show_lines {
+            true => outcome.winning_chips.unwrap_or_default(),
+            false => HashSet::new(),
+        };
+        Some(Preview {
+            player,
+            cell: (column, outcome.row),
+            lines,
+        })
     }
+}
 
-    pub fn check_win(
-        // returns bool. If needed, switch back to returning winning direction if present
-        &self,
-        col: usize,
-        row: usize,
-        player: &Player,
-        win_length: usize,
-    ) -> bool {
-        let directions = vec![(1, 1), (1, 0), (1, -1), (0, -1)];
-        for (dx, dy) in directions {
-            if self.check_line(col, row, dx, dy, player, win_length) {
-                return true;
-            }
-            // log::info!("Checked line!");
-        }
-        false
+fn roll_button(on_roll: &Callback<Move>, mv: Move, label: &'static str) -> Html {
+    let on_roll = on_roll.clone();
+    html! {
+        <button class="rollbutton" onclick={Callback::from(move |_| on_roll.emit(mv))}>{label}</button>
     }
+}
 
-    fn find_adjacent_chips_in_dir(
-        &self,
-        col: usize,
-        row: usize,
-        direction: (i32, i32),
-        player: &Player,
-    ) -> HashSet<(usize, usize)> {
-        let mut found_positions = HashSet::new();
-        let mut s = 0;
-        loop {
-            // first find all consecutive
-            let (x, y) = self.shift_coords((col, row), direction, s);
-            if found_positions.contains(&(x, y)) {
-                // if we loop all the way around, we can return
-                // TODO: this actually isn't necessary: this situation only occurs when a full "loop"
-                // (aka row/column/snake through diagonals) is filled, however, if that loop is only missing 1 spot,
-                // there is already a winning connect line as long as every loop is at least one longer than the win-length
-                // (having the width and height > win_length guarantees this is the case), hence you can never complete the
-                // loop
-                // Keeping the HashSet is still useful though, since we use it to look up if a chip is winning
-                return found_positions;
+#[function_component(BoardView)]
+pub fn board_view(boardprops: &BoardProps) -> Html {
+    let view_mode = use_state(|| BoardViewMode::Flat);
+    // move of the button the mouse or focus is on, previewed on the board
+    let hovered_move = use_state(|| None::<Move>);
+    // column number typed so far on the keyboard, see `board_keydown`
+    let typed_digits = use_mut_ref(String::new);
+    let show_lines = use_state(|| false);
+    let settings = use_state(Settings::load);
+    let marked_moves = use_state(|| 1);
+    let marked_moves_change = {
+        let marked_moves = marked_moves.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+            if let Some(count) = select.and_then(|select| select.value().parse().ok()) {
+                marked_moves.set(count);
             }
-            if self.board[x][y].as_ref() == Some(player) {
-                found_positions.insert((x, y));
-            } else {
-                break; // consecutive chain broken
+        })
+    };
+    let show_threats = use_state(|| false);
+    let show_threats_change = {
+        let show_threats = show_threats.clone();
+        Callback::from(move |_: Event| show_threats.set(!*show_threats))
+    };
+    let reduce_motion_change = {
+        let settings = settings.clone();
+        Callback::from(move |_: Event| {
+            let new_settings = Settings {
+                reduce_motion: !settings.reduce_motion,
+                ..(*settings).clone()
+            };
+            new_settings.save();
+            new_settings.apply();
+            settings.set(new_settings);
+        })
+    };
+    let show_lines_change = {
+        let show_lines = show_lines.clone();
+        Callback::from(move |_: Event| show_lines.set(!*show_lines))
+    };
+    let view_mode_change = {
+        let view_mode = view_mode.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+            if let Some(mode) = select
+                .and_then(|select| select.value().parse::<usize>().ok())
+                .and_then(|index| BoardViewMode::ALL.get(index))
+            {
+                view_mode.set(*mode);
             }
-            s += 1;
+        })
+    };
+    let shown_mode = Some(*view_mode)
+        .filter(|mode| mode.plays(&boardprops.variants))
+        .unwrap_or(BoardViewMode::Flat);
+    let board_html = match shown_mode {
+        BoardViewMode::Flat => {
+            let preview = hovered_move.and_then(|mv| Preview::new(boardprops, mv, *show_lines));
+            let dropped = dropped_chip(boardprops).filter(|_| !settings.reduce_motion);
+            let overlays = Overlays {
+                markers: move_markers(&boardprops.moves, *marked_moves, played_cell),
+                popped: move_markers(&boardprops.moves, *marked_moves, popped_column),
+                threats: match *show_threats && boardprops.winning_chips.is_none() {
+                    true => threat_cells(boardprops),
+                    false => HashMap::new(),
+                },
+            };
+            flat_board(
+                boardprops,
+                &hovered_move,
+                preview,
+                dropped,
+                overlays,
+                typed_digits,
+            )
         }
-        s = -1;
-        loop {
-            let (x, y) = self.shift_coords((col, row), direction, s);
-            // since we did not return yet, we know there is one opponent chip/empty spot in the line
-            // so we don't need to check whether we loop around
-            if self.board[x][y].as_ref() == Some(player) {
-                found_positions.insert((x, y));
-            } else {
-                break;
+        BoardViewMode::Tiled => html! {
+            <TiledBoardView board={boardprops.board.clone()} winning_chips={boardprops.winning_chips.clone()}
+                win_length={boardprops.win_length}
+                column_callbacks={boardprops.column_callbacks.clone()}/>
+        },
+        BoardViewMode::Torus => html! {
+            <TorusView board={boardprops.board.clone()} winning_chips={boardprops.winning_chips.clone()}
+                column_callbacks={boardprops.column_callbacks.clone()}/>
+        },
+    };
+
+    html! {
+        <div>
+            <select class="viewselect" onchange={view_mode_change}>
+                {BoardViewMode::ALL.iter().enumerate().map(|(index, mode)| html! {
+                    <option value={index.to_string()} selected={*mode == shown_mode}
+                        disabled={!mode.plays(&boardprops.variants)}>{mode.label()}</option>
+                }).collect::<Html>()}
+            </select>
+            if boardprops.turn_player.is_some() && shown_mode == BoardViewMode::Flat {
+                <label class="viewselect">
+                    <input type="checkbox" checked={*show_lines} onchange={show_lines_change}/>
+                    {t("board.show_lines")}
+                </label>
             }
-            s -= 1;
-        }
-        found_positions
+            <label class="viewselect">
+                <input type="checkbox" checked={settings.reduce_motion} onchange={reduce_motion_change}/>
+                {t("board.reduce_motion")}
+            </label>
+            if shown_mode == BoardViewMode::Flat {
+                <select class="viewselect" onchange={marked_moves_change}>
+                    {MARKED_MOVES_CHOICES.iter().map(|count| html! {
+                        <option value={count.to_string()} selected={*count == *marked_moves}>
+                            {match count {
+                                0 => t("board.no_marks"),
+                                _ => tn("board.mark_moves", *count, &[]),
+                            }}
+                        </option>
+                    }).collect::<Html>()}
+                </select>
+                <label class="viewselect">
+                    <input type="checkbox" checked={*show_threats} onchange={show_threats_change}/>
+                    {t("board.show_threats")}
+                </label>
+            }
+            {board_html}
+        </div>
     }
+}
 
-    pub fn find_winning_chips(
-        &self,
-        col: usize,
-        row: usize,
-        player: &Player,
-        win_length: usize,
-    ) -> HashSet<(usize, usize)> {
-        let directions = vec![(1, 1), (1, 0), (1, -1), (0, -1)]; // TODO copied code
-        let mut found_positions = HashSet::new();
-        for dir in directions {
-            let adj_chips_in_dir = self.find_adjacent_chips_in_dir(col, row, dir, player);
-            if adj_chips_in_dir.len() >= win_length {
-                // in this case the adjacent chips are a winning line
-                // TODO note that now the check_line and check win methods are essentially redundant,
-                // (except for being somewhat more efficient?)
-                found_positions.extend(self.find_adjacent_chips_in_dir(col, row, dir, player));
+/// Keyboard controls of the flat board: the arrow keys move the focus between the columns, wrapping
+/// around like the board, and typing a column's number drops a chip in it. On boards of 10 or more
+/// columns a digit that can start a longer number only focuses its column until the next key, so
+/// "1" "2" drops in column 12 and "1" Enter in column 1. `typed` holds the digits so far.
+fn board_keydown(e: KeyboardEvent, typed: &RefCell<String>) {
+    let Some(grid) = e
+        .current_target()
+        .and_then(|t| t.dyn_into::<Element>().ok())
+    else {
+        return;
+    };
+    let Ok(columns) = grid.query_selector_all(".column") else {
+        return;
+    };
+    let count = columns.length();
+    let column = |index: u32| {
+        columns
+            .get(index)
+            .and_then(|node| node.dyn_into::<HtmlElement>().ok())
+    };
+    let target = e.target().and_then(|t| t.dyn_into::<Node>().ok());
+    let focused = (0..count).find(|&index| {
+        columns
+            .get(index)
+            .is_some_and(|node| node.is_same_node(target.as_ref()))
+    });
+    match e.key().as_str() {
+        "ArrowLeft" | "ArrowRight" => {
+            let step = if e.key() == "ArrowLeft" { count - 1 } else { 1 };
+            let next = focused.map_or(0, |index| (index + step) % count);
+            typed.borrow_mut().clear();
+            if let Some(column) = column(next) {
+                e.prevent_default();
+                let _ = column.focus();
             }
         }
-        found_positions
+        key if key.len() == 1 && key.chars().all(|c| c.is_ascii_digit()) => {
+            let mut typed = typed.borrow_mut();
+            typed.push_str(key);
+            let mut number = typed.parse::<u32>().unwrap_or(0);
+            if !(1..=count).contains(&number) {
+                // not a column, start over from this digit
+                *typed = key.to_owned();
+                number = typed.parse().unwrap_or(0);
+            }
+            let Some(column) = number.checked_sub(1).and_then(column) else {
+                typed.clear();
+                return;
+            };
+            e.prevent_default();
+            let _ = column.focus();
+            if number * 10 > count {
+                typed.clear();
+                column.click();
+            }
+        }
+        _ => typed.borrow_mut().clear(),
     }
 }
 
-#[derive(PartialEq, Properties)]
-pub struct BoardProps {
-    pub board: Board,
-    pub winning_chips: Option<HashSet<(usize, usize)>>,
-    pub column_callbacks: Vec<Callback<MouseEvent>>, // define in gamedata component as  ctx.link().callback(move |_| Msg::ColumnClick(colnr))
+/// Marks drawn over the cells of the flat board
+struct Overlays {
+    markers: HashMap<(usize, usize), MoveMarker>,
+    /// Columns a chip was popped out of, the marks go below them
+    popped: HashMap<usize, MoveMarker>,
+    threats: HashMap<(usize, usize), Player>,
 }
 
-#[function_component(BoardView)]
-pub fn board_view(boardprops: &BoardProps) -> Html {
+/// The board drawn once, columns are buttons and the variants add buttons around them. Hovering or
+/// focusing a column or push button shows the `preview` of that move. The `dropped` chip is animated.
+fn flat_board(
+    boardprops: &BoardProps,
+    hovered_move: &UseStateHandle<Option<Move>>,
+    preview: Option<Preview>,
+    dropped: Option<((usize, usize), i32)>,
+    overlays: Overlays,
+    typed_digits: Rc<RefCell<String>>,
+) -> Html {
     let board_html = boardprops
         .board
         .board
@@ -197,30 +423,103 @@ pub fn board_view(boardprops: &BoardProps) -> Html {
             // let mut columnstr = String::new();
             let mut column_cells = html! {};
             for (row, cell_status) in column.iter().enumerate() {
-                let winning;
-                if boardprops.winning_chips.is_some() {
-                    winning = (&boardprops.winning_chips)
-                        .as_ref()
-                        .unwrap()
-                        .contains(&(colnr, row));
-                } else {
-                    winning = false;
-                }
+                let winning = match &boardprops.winning_chips {
+                    Some(winning_chips) => winning_chips.contains(&(colnr, row)),
+                    None => false,
+                };
 
                 column_cells = html! { // prepend new cell to existing html
                     <>
-                        <Cell status={cell_status.clone()} winning={winning}/>
+                        <Cell status={cell_status.clone()} winning={winning} coords={(colnr, row)}
+                            ghost={preview.as_ref().filter(|p| p.cell == (colnr, row)).map(|p| p.player.clone())}
+                            highlighted={preview.as_ref().is_some_and(|p| p.lines.contains(&(colnr, row)))}
+                            drop_from={dropped.filter(|(cell, _)| *cell == (colnr, row)).map(|(_, cells)| cells)}
+                            move_marker={overlays.markers.get(&(colnr, row)).copied()}
+                            threat={overlays.threats.get(&(colnr, row)).cloned()}/>
                         {column_cells}
                     </>
                 }
             }
             // let columnstr = "kaas";
             let on_column_click = &boardprops.column_callbacks[colnr];
-            html! {<button class="column" onclick={on_column_click}>
+            let hover = |mv: Move| {
+                let hovered_move = hovered_move.clone();
+                Callback::from(move |_: Event| hovered_move.set(Some(mv)))
+            };
+            let unhover = |mv: Move| {
+                let hovered_move = hovered_move.clone();
+                Callback::from(move |_: Event| {
+                    if *hovered_move == Some(mv) {
+                        hovered_move.set(None);
+                    }
+                })
+            };
+            let drop = Move::Drop(colnr);
+            let column_html = html! {<button class="column" onclick={on_column_click}
+                onmouseenter={hover(drop).reform(Event::from)} onfocus={hover(drop).reform(Event::from)}
+                onmouseleave={unhover(drop).reform(Event::from)} onblur={unhover(drop).reform(Event::from)}>
                 {column_cells}
-            </button>}
+            </button>};
+            let push = Move::DropFromBottom(colnr);
+            let popped = overlays.popped.get(&colnr).copied();
+            if boardprops.pop_callbacks.is_none()
+                && boardprops.push_callbacks.is_none()
+                && boardprops.on_roll.is_none()
+                && popped.is_none()
+            {
+                return column_html;
+            }
+            let roll = |up, label| match &boardprops.on_roll {
+                Some(on_roll) => {
+                    roll_button(on_roll, Move::RollColumn { column: colnr, up }, label)
+                }
+                None => html! {},
+            };
+            html! {
+                <div class="boardcolumn">
+                    {roll(true, "▲")}
+                    {column_html}
+                    {roll(false, "▼")}
+                    if let Some(marker) = popped {
+                        {pop_marker(marker)}
+                    }
+                    if let Some(pop_callbacks) = &boardprops.pop_callbacks {
+                        <button class="popbutton" onclick={&pop_callbacks[colnr]}>{t("board.pop")}</button>
+                    }
+                    if let Some(push_callbacks) = &boardprops.push_callbacks {
+                        <button class="popbutton" onclick={&push_callbacks[colnr]}
+                            onmouseenter={hover(push).reform(Event::from)} onfocus={hover(push).reform(Event::from)}
+                            onmouseleave={unhover(push).reform(Event::from)} onblur={unhover(push).reform(Event::from)}>
+                            {t("board.push")}
+                        </button>
+                    }
+                </div>
+            }
         })
         .collect::<Html>();
 
-    board_html
+    // one extra grid column for the row arrows
+    let row_rolls_html = match &boardprops.on_roll {
+        Some(on_roll) => html! {
+            <div class="rollrows">
+                {(0..boardprops.board.height as usize).rev().map(|row| html! {
+                    <div class="rollrow">
+                        {roll_button(on_roll, Move::RollRow { row, right: false }, "◀")}
+                        {roll_button(on_roll, Move::RollRow { row, right: true }, "▶")}
+                    </div>
+                }).collect::<Html>()}
+            </div>
+        },
+        None => html! {},
+    };
+    let grid_columns = boardprops.board.width as usize + usize::from(boardprops.on_roll.is_some());
+
+    html! {
+        // cells shrink to fit the board on small screens, see .cell in gridconnect.css
+        <div class="grid" style={format!("grid-template-columns: repeat({0}, fit-content(100%)); --cell-size: min(100px, calc(80vw / {0}));", grid_columns)}
+            onkeydown={Callback::from(move |e| board_keydown(e, &typed_digits))} aria-label={t("board.label")}>
+            {board_html}
+            {row_rolls_html}
+        </div>
+    }
 }
@@ -1,4 +1,7 @@
-use crate::Player;
+use crate::i18n::{t, tf};
+use common::game::GameData;
+use common::rules::{Move, PlayedMove};
+use common::Player;
 use yew::prelude::*;
 
 fn square_with_hole_svg(sidelength: f32, radius: f32) -> Html {
@@ -26,50 +29,184 @@ fn square_with_hole_svg(sidelength: f32, radius: f32) -> Html {
     }
 }
 
-fn chip(player: &Player, winning: bool) -> Html {
-    let (mut fill_color, rim_color) = match *player {
-        Player::One => ("var(--red)", "var(--darkred)"),
-        Player::Two => ("var(--blue)", "var(--darkblue)"),
+/// Colour of the player's chips, also used as css class for their status messages
+pub fn player_color(player: &Player) -> &'static str {
+    match player {
+        Player::One => "red",
+        Player::Two => "blue",
+        Player::Three => "purple",
+        Player::Four => "orange",
+    }
+}
+
+/// What a move did, for screen readers, e.g. "Player 1 dropped a chip in column 3, row 2"
+fn describe_move(played: &PlayedMove) -> String {
+    let (key, line) = match played.mv {
+        Move::Drop(column) => ("move.drop", column),
+        Move::DropFromBottom(column) => ("move.push", column),
+        Move::Pop(column) => ("move.pop", column),
+        Move::RollColumn { column, up: true } => ("move.roll_up", column),
+        Move::RollColumn { column, up: false } => ("move.roll_down", column),
+        Move::RollRow { row, right: true } => ("move.roll_right", row),
+        Move::RollRow { row, right: false } => ("move.roll_left", row),
+    };
+    let action = tf(key, &[&u8::from(played.player.clone()), &(line + 1)]);
+    match played.row {
+        Some(row) => action + &tf("move.row", &[&(row + 1)]),
+        None => action,
+    }
+}
+
+/// "Player n's turn", "Player n won!" in the winner's colour or a draw once the game is over. A hidden
+/// live region also announces the last move and the new status to screen readers.
+pub fn status_html(game: &GameData) -> Html {
+    let win_status = game.win_status.as_ref();
+    let status = match win_status {
+        None if game.is_draw() => t("game.draw"),
+        None => tf("game.turn", &[&u8::from(game.turn_player.clone())]),
+        Some(winner) => tf("game.won", &[&u8::from(winner.clone())]),
+    };
+    let announcement = match game.moves.last() {
+        Some(played) => format!("{}. {}", describe_move(played), status),
+        None => status.clone(),
     };
+    html! {
+        <>
+            <div class={classes!("smallblock", win_status.map(player_color))}>{status}</div>
+            <div class="visuallyhidden" aria-live="polite">{announcement}</div>
+        </>
+    }
+}
+
+/// Symbol drawn on the player's chips, only shown if the chip patterns setting is on
+fn chip_pattern(player: &Player) -> Html {
+    let shape = match player {
+        Player::One => html! {<path d="M 26 40 h 48 M 24 50 h 52 M 26 60 h 48"/>},
+        Player::Two => html! {<path d="M 36 36 L 64 64 M 36 64 L 64 36"/>},
+        Player::Three => html! {<polygon points="50,32 67,62 33,62"/>},
+        Player::Four => html! {<rect x=37 y=37 width=26 height=26/>},
+    };
+    html! {
+        <g class="chippattern" stroke={format!("var(--dark{})", player_color(player))}>{shape}</g>
+    }
+}
 
-    let stroke_width;
-    if winning {
+fn chip(player: &Player, winning: bool) -> Html {
+    let color = player_color(player);
+    let mut fill_color = format!("var(--{})", color);
+    let rim_color = format!("var(--dark{})", color);
+
+    let stroke_width = if winning {
         // rim_color = "#00A000";
-        fill_color = "#2db020";
-        stroke_width = "16%";
+        fill_color = "var(--winning)".to_owned();
+        "16%"
     } else {
-        stroke_width = "10%";
-    }
+        "10%"
+    };
 
     html! {
         <g>
             <circle cx="50" cy="50" r="32.5" fill={fill_color} stroke={rim_color}
             stroke-width={stroke_width} />
+            {chip_pattern(player)}
         </g>
     }
 }
 
+/// Drawing of a 100x100 cell of the board with the chip in it, if any. An empty cell can show a
+/// see-through `ghost` chip of the player that is about to play there. A chip that was just played
+/// falls in from `drop_from` cells above, or below if negative.
+pub fn cell_svg(
+    status: Option<&Player>,
+    winning: bool,
+    ghost: Option<&Player>,
+    drop_from: Option<i32>,
+) -> Html {
+    html! {
+        <>
+            <circle cx=50 cy=50 r=40 fill="var(--background-color)"/>
+            if let Some(player) = status {
+                <g class={classes!(drop_from.is_some().then_some("droppingchip"), winning.then_some("winningchip"))}
+                    style={drop_from.map(|cells| format!("--drop-from: {}px;", -100 * cells))}>
+                    {chip(player, winning)}
+                </g>
+            } else if let Some(player) = ghost {
+                <g class="ghostchip">{chip(player, false)}</g>
+            }
+            {square_with_hole_svg(100., 32.5)}
+        </>
+    }
+}
+
 #[derive(PartialEq, Properties)]
 pub struct CellProps {
     pub status: Option<Player>,
     pub winning: bool,
+    /// Column and row of the cell on the board, for its label
+    pub coords: (usize, usize),
+    /// Chip previewed in the cell, for the column the mouse or focus is on
+    #[prop_or_default]
+    pub ghost: Option<Player>,
+    /// Marks the cell as part of a line the previewed chip would complete
+    #[prop_or_default]
+    pub highlighted: bool,
+    /// Set if the chip was just dropped, see `cell_svg`
+    #[prop_or_default]
+    pub drop_from: Option<i32>,
+    /// Set if one of the recent moves was played here, see `MoveMarker`
+    #[prop_or_default]
+    pub move_marker: Option<MoveMarker>,
+    /// Player that would complete a line by playing here
+    #[prop_or_default]
+    pub threat: Option<Player>,
+}
+
+/// Mark on a cell that was played in recently
+#[derive(PartialEq, Clone, Copy)]
+pub enum MoveMarker {
+    /// The last move, when it is the only one marked
+    Dot,
+    /// Number of moves ago
+    Age(usize),
+}
+
+/// "Column 3, row 2: player 1", rows are counted from the bottom
+fn cell_label(props: &CellProps) -> String {
+    let (column, row) = props.coords;
+    let owner = match &props.status {
+        Some(player) => tf("cell.player", &[&u8::from(player.clone())]),
+        None => t("cell.empty"),
+    };
+    let winning = if props.winning {
+        t("cell.winning")
+    } else {
+        String::new()
+    };
+    tf("cell.label", &[&(column + 1), &(row + 1), &owner]) + &winning
 }
 
 #[function_component(Cell)]
 pub fn cell(props: &CellProps) -> Html {
     // let chip_html = ;
     html! {
-        <div class="cell">
-            <svg height="100" width="100">
-                <circle cx=50 cy=50 r=40 fill="var(--background-color)"/>
-                {
-                    if let Some(player) = &props.status {
-                    chip(&player, props.winning)
-                    } else {
-                        html!{}
-                    }
+        <div class="cell" role="img" aria-label={cell_label(props)}>
+            <svg viewBox="0 0 100 100">
+                {cell_svg(props.status.as_ref(), props.winning, props.ghost.as_ref(), props.drop_from)}
+                if props.highlighted {
+                    <circle class="previewline" cx=50 cy=50 r=40/>
+                }
+                if let Some(player) = &props.threat {
+                    <circle class="threat" cx=50 cy=50 r=24 stroke={format!("var(--{})", player_color(player))}/>
                 }
-                {square_with_hole_svg(100., 32.5)}
+                {match props.move_marker {
+                    Some(MoveMarker::Dot) => html! {<circle class="movemarker" cx=50 cy=50 r=8/>},
+                    Some(MoveMarker::Age(age)) => html! {
+                        <text class="movemarker" x=50 y=50 text-anchor="middle" dominant-baseline="central">
+                            {age}
+                        </text>
+                    },
+                    None => html! {},
+                }}
                 // <rect width="100%" height="100%" style="fill:rgb(0,0,255)" />
             </svg>
         </div>
@@ -0,0 +1,254 @@
+use crate::cookies::get_player_id;
+use crate::database::{get_object, send_chat_message};
+use crate::i18n::{chat_result_text, t};
+use crate::profile::player_link;
+use crate::storage;
+use common::chat::{ChatChannel, ChatMessage, ChatResult, NewChatMessage, MAX_MESSAGE_LENGTH};
+use common::IdType;
+use gloo_timers::callback::Interval;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{EventTarget, HtmlInputElement};
+use yew::prelude::*;
+
+/// Milliseconds between checks for new messages
+const POLL_INTERVAL: u32 = 2000;
+/// Local storage key of the players this browser muted
+const MUTED_KEY: &str = "muted_players";
+
+/// Messages of the channel being read, new ones are appended as they come in
+#[derive(PartialEq)]
+struct ChatLog {
+    channel: ChatChannel,
+    messages: Vec<ChatMessage>,
+}
+
+enum ChatLogAction {
+    /// Starts over with another channel
+    Switch(ChatChannel),
+    Append(Vec<ChatMessage>),
+}
+
+impl ChatLog {
+    fn last_id(&self) -> u64 {
+        self.messages.last().map_or(0, |message| message.message_id)
+    }
+}
+
+impl Reducible for ChatLog {
+    type Action = ChatLogAction;
+
+    fn reduce(self: Rc<Self>, action: ChatLogAction) -> Rc<Self> {
+        match action {
+            ChatLogAction::Switch(channel) => Rc::new(ChatLog {
+                channel,
+                messages: Vec::new(),
+            }),
+            // polls can overlap or answer for the channel read before, so skip what doesn't fit
+            ChatLogAction::Append(new_messages) => {
+                let last_id = self.last_id();
+                let mut messages = self.messages.clone();
+                messages.extend(new_messages.into_iter().filter(|message| {
+                    message.channel == self.channel && message.message_id > last_id
+                }));
+                Rc::new(ChatLog {
+                    channel: self.channel,
+                    messages,
+                })
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ChatProps {
+    pub game_id: IdType,
+    /// Whether the viewer has a seat in the game, they write in the players' channel
+    pub seated: bool,
+}
+
+/// Chat of a lobby and its game. Anyone can read both channels, but only write in their own one.
+/// Muted players are remembered in this browser, their messages are collapsed.
+#[function_component(ChatPanel)]
+pub fn chat_panel(props: &ChatProps) -> Html {
+    let own_channel = ChatChannel::for_sender(props.seated);
+    let log = use_reducer(|| ChatLog {
+        channel: own_channel,
+        messages: Vec::new(),
+    });
+    {
+        // the game is only known after a fetch, so the viewer can turn out to be seated later on
+        let log = log.clone();
+        use_effect_with_deps(
+            move |own_channel| {
+                if log.channel != *own_channel {
+                    log.dispatch(ChatLogAction::Switch(*own_channel));
+                }
+                || ()
+            },
+            own_channel,
+        );
+    }
+    // read by the poll, which outlives the render it was set up in
+    let last_id = use_mut_ref(|| 0);
+    *last_id.borrow_mut() = log.last_id();
+    let muted = use_state(|| storage::load::<Vec<IdType>>(MUTED_KEY).unwrap_or_default());
+    let text = use_state(String::new);
+    let send_result = use_state(|| None::<ChatResult>);
+
+    {
+        let channel = log.channel;
+        let log = log.clone();
+        use_effect_with_deps(
+            move |(game_id, channel)| {
+                let (game_id, channel) = (*game_id, *channel);
+                let poll = move || {
+                    let url = format!(
+                        "/api/chat/{}/{}/{}",
+                        game_id,
+                        u8::from(channel),
+                        last_id.borrow()
+                    );
+                    let log = log.clone();
+                    spawn_local(async move {
+                        match get_object::<Vec<ChatMessage>>(&url).await {
+                            Ok(messages) if !messages.is_empty() => {
+                                log.dispatch(ChatLogAction::Append(messages))
+                            }
+                            _ => (),
+                        }
+                    });
+                };
+                poll();
+                let interval = Interval::new(POLL_INTERVAL, poll);
+                move || drop(interval)
+            },
+            (props.game_id, channel),
+        );
+    }
+
+    let switch_channel = |channel: ChatChannel| {
+        let log = log.clone();
+        Callback::from(move |_| log.dispatch(ChatLogAction::Switch(channel)))
+    };
+    let toggle_mute = |player_id: IdType| {
+        let muted = muted.clone();
+        Callback::from(move |_| {
+            let mut new_muted = (*muted).clone();
+            match new_muted.iter().position(|id| *id == player_id) {
+                Some(index) => {
+                    new_muted.remove(index);
+                }
+                None => new_muted.push(player_id),
+            }
+            storage::save(MUTED_KEY, &new_muted);
+            muted.set(new_muted);
+        })
+    };
+    let oninput = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            let target: Option<EventTarget> = e.target();
+            if let Some(input) = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
+                text.set(input.value());
+            }
+        })
+    };
+    let onsubmit = {
+        let text = text.clone();
+        let send_result = send_result.clone();
+        let game_id = props.game_id;
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let message = NewChatMessage {
+                channel: own_channel,
+                text: (*text).clone(),
+            };
+            if !message.is_valid() {
+                send_result.set(Some(ChatResult::InvalidMessage));
+                return;
+            }
+            let text = text.clone();
+            let send_result = send_result.clone();
+            spawn_local(async move {
+                match send_chat_message(game_id, &message)
+                    .await
+                    .unwrap_or(ChatResult::Failed)
+                {
+                    ChatResult::Sent => {
+                        text.set(String::new());
+                        send_result.set(None);
+                    }
+                    result => send_result.set(Some(result)),
+                }
+            });
+        })
+    };
+
+    let player_id = get_player_id();
+    let messages_html = log
+        .messages
+        .iter()
+        .map(|message| {
+            let sender_id = message.sender.player_id;
+            let is_muted = muted.contains(&sender_id);
+            html! {
+                <div class="chatmessage">
+                    <span class="chattime">{&message.sent_at}</span>
+                    {player_link(&message.sender)}
+                    if is_muted {
+                        <i>{t("chat.hidden")}</i>
+                    } else {
+                        {&message.text}
+                    }
+                    if sender_id != player_id {
+                        <button class="chatmute" onclick={toggle_mute(sender_id)}>
+                            {t(if is_muted { "chat.unmute" } else { "chat.mute" })}
+                        </button>
+                    }
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="smallblock chat">
+            <div>
+                {ChatChannel::ALL.iter().map(|channel| html! {
+                    <button class={classes!("chattab", (*channel == log.channel).then_some("active"))}
+                        onclick={switch_channel(*channel)}>
+                        {t(match channel {
+                            ChatChannel::Players => "chat.players",
+                            ChatChannel::Spectators => "chat.spectators",
+                        })}
+                    </button>
+                }).collect::<Html>()}
+            </div>
+            <div class="chatmessages" aria-live="polite">
+                if log.messages.is_empty() {
+                    <i>{t("chat.empty")}</i>
+                }
+                {messages_html}
+            </div>
+            if log.channel == own_channel {
+                <form {onsubmit}>
+                    <input type="text" placeholder={t("chat.placeholder")} value={(*text).clone()} {oninput}
+                        maxlength={MAX_MESSAGE_LENGTH.to_string()}/>
+                    <button type="submit">{t("chat.send")}</button>
+                    <span class="chatcount">
+                        {format!("{}/{}", text.chars().count(), MAX_MESSAGE_LENGTH)}
+                    </span>
+                </form>
+                if let Some(result) = *send_result {
+                    <p>{chat_result_text(&result)}</p>
+                }
+            } else {
+                <p>{t(match own_channel {
+                    ChatChannel::Players => "chat.players_write_elsewhere",
+                    ChatChannel::Spectators => "chat.players_only",
+                })}</p>
+            }
+        </div>
+    }
+}
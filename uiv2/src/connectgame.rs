@@ -1,96 +1,39 @@
+use crate::cell::{player_color, status_html};
+use crate::chat::ChatPanel;
 use crate::cookies::get_player_id;
-use crate::IdType;
-use crate::{database::get_object, Board, BoardView, Player};
+use crate::i18n::{t, tf};
+use crate::offline::{is_offline, OfflineNotice};
+use crate::profile::use_player_infos;
+use crate::{
+    database::{answer_takeback, get_object, post_object, request_takeback},
+    BoardView,
+};
+use common::game::GameData;
+use common::lobby::FirstPlayer;
+use common::rules::{Move, Variants};
+use common::{IdType, Player};
 use gloo_timers::callback::Timeout;
-use reqwasm::http::Request;
-use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::HashSet, rc::Rc};
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize, Properties)]
-pub struct GameData {
-    // TODO change usizes to fixed size (in Board too!)
-    pub game_id: IdType,
-    pub board: Board,
-    pub win_length: u8,
-    pub turn_player: Player,
-    pub win_status: Option<Player>,
-    pub winning_chips: Option<HashSet<(usize, usize)>>,
-    pub player1_id: IdType,
-    pub player2_id: IdType,
-}
-
-impl GameData {
-    pub fn new(
-        width: u8,
-        height: u8,
-        win_length: u8,
-        game_id: IdType,
-        player1_id: IdType,
-        player2_id: IdType,
-    ) -> Self {
-        Self {
-            game_id,
-            board: Board::new(width, height),
-            win_length,
-            turn_player: Player::One,
-            win_status: None,
-            winning_chips: None,
-            player1_id,
-            player2_id,
-        }
-    }
-
-    // pub fn new_round(gamedata: GameData) {
-    //     Self {
-    //         board: Board::new(gamedata.board.width, gamedata.board.height),
-    //         turn_player: gamedata.
-    //     }
-    // }
-
-    fn turn_player_id(&self) -> IdType {
-        match self.turn_player {
-            Player::One => self.player1_id,
-            Player::Two => self.player2_id,
-        }
-    }
-
-    // pub fn reset(&mut self) {
-    //     let _ = mem::replace(
-    //         self,
-    //         Self::new(self.board.width, self.board.height, self.win_length),
-    //     );
-    // }
-    // pub fn replace(&mut self, replacement: Self) {
-    //     let _ = mem::replace(self, replacement);
-    // }
-
-    // pub fn check_win(&self, col: usize, row: usize, player: &Player) -> Option<(i32, i32)> {
-    //     self.board.check_win(col, row, player, self.win_length)
-    // }
-
-    pub fn next_turn(&mut self) {
-        self.turn_player = match self.turn_player {
-            Player::One => Player::Two,
-            Player::Two => Player::One,
-        }
-    }
-}
-
 #[derive(PartialEq)]
 pub enum FetchGameData {
     NotFetching,
     Fetching,
     Success(GameData),
     Failed,
+    /// The browser has no connection, the last fetched game is shown until it is back
+    Offline,
     InvalidId,
 }
 
 pub enum ConnectMsg {
-    ColumnClick(usize),
+    Play(Move),
     SetFetchState(FetchGameData),
     Reset,
+    Resign,
+    RequestTakeback,
+    AnswerTakeback(bool),
     GetData,
     Tick,
 }
@@ -100,6 +43,30 @@ pub struct ConnectProps {
     pub game_id: IdType,
 }
 
+#[derive(PartialEq, Properties)]
+struct GamePlayersProps {
+    player_ids: Vec<IdType>,
+}
+
+/// Seat number, name and avatar of each player, their infos are fetched together
+#[function_component(GamePlayers)]
+fn game_players(props: &GamePlayersProps) -> Html {
+    let player_infos = use_player_infos(props.player_ids.clone());
+    props
+        .player_ids
+        .iter()
+        .zip(Player::ALL)
+        .map(|(player_id, player)| {
+            html! {
+                <span class={player_color(&player)}>
+                    {tf("game.seat", &[&u8::from(player.clone())])}
+                    {player_infos.link(*player_id)}
+                </span>
+            }
+        })
+        .collect::<Html>()
+}
+
 pub struct ConnectGame {
     fetch_game_data: FetchGameData,
     game_data_cache: GameData,
@@ -109,14 +76,29 @@ impl ConnectGame {
     fn reset(&mut self) {
         // TODO Temp solution, might remove
         self.fetch_game_data = FetchGameData::NotFetching;
-        self.game_data_cache = GameData::new(
-            7,
-            6,
-            4,
-            self.game_data_cache.game_id,
-            self.game_data_cache.player1_id,
-            self.game_data_cache.player2_id,
-        );
+        self.game_data_cache = self.game_data_cache.new_round();
+    }
+
+    /// The last fetched game and the seat of this player in it, if they play in it
+    fn own_seat(&self) -> Option<(GameData, Player)> {
+        match &self.fetch_game_data {
+            FetchGameData::Success(data) => {
+                let player = data.seat_of(get_player_id())?;
+                Some((data.clone(), player))
+            }
+            _ => None,
+        }
+    }
+
+    /// Sends the new game state to the server and fetches it again
+    fn save(ctx: &Context<Self>, game_data: GameData) {
+        spawn_local(async move {
+            if let Err(err) = post_object("/api/save_game", game_data).await {
+                log::info!("Failed to save game: {}", err);
+            }
+        });
+        ctx.link()
+            .send_message(ConnectMsg::SetFetchState(FetchGameData::NotFetching));
     }
 }
 
@@ -133,15 +115,16 @@ impl Component for ConnectGame {
                 6,
                 4,
                 ctx.props().game_id,
-                0, //TODO this is not ideal ofc
-                0,
+                vec![0, 0], //TODO this is not ideal ofc
+                FirstPlayer::Host,
+                Variants::default(),
             ),
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         if self.fetch_game_data == FetchGameData::InvalidId {
-            return html! {<h2>{"This game does not exist"}</h2>};
+            return html! {<h2>{t("game.not_found")}</h2>};
         }
 
         if self.fetch_game_data == FetchGameData::NotFetching {
@@ -153,43 +136,99 @@ impl Component for ConnectGame {
             _ => &self.game_data_cache,
         };
 
-        let turn_player_html = match game_data.turn_player {
-            Player::One => html! {<div class="smallblock">{"Player 1's turn"}</div>},
-            Player::Two => html! {<div class="smallblock">{"Player 2's turn"}</div>},
-        };
-
-        let status_html = match game_data.win_status {
-            None => turn_player_html,
-            Some(Player::One) => {
-                html! {<div class="smallblock red">{"Player 1 won!"}</div>}
-            }
-            Some(Player::Two) => {
-                html! {<div class="smallblock blue">{"Player 2 won!"}</div>}
+        let status_html = status_html(game_data);
+        let own_seat = game_data.seat_of(get_player_id());
+        let can_resign = !game_data.is_over()
+            && own_seat
+                .as_ref()
+                .is_some_and(|player| !game_data.eliminated.contains(player));
+        let can_request_takeback = own_seat
+            .as_ref()
+            .is_some_and(|player| game_data.can_request_takeback(player));
+        let can_answer_takeback = own_seat
+            .as_ref()
+            .is_some_and(|player| game_data.can_answer_takeback(player));
+
+        let takeback_html = match &game_data.takeback_request {
+            Some(requester) if can_answer_takeback => html! {
+                <div class="smallblock">
+                    {tf("game.takeback_request", &[&u8::from(requester.clone())])}
+                    <button class="greenbutton" onclick={ctx.link().callback(|_| ConnectMsg::AnswerTakeback(true))}>
+                        {t("game.accept")}
+                    </button>
+                    <button class="greenbutton" onclick={ctx.link().callback(|_| ConnectMsg::AnswerTakeback(false))}>
+                        {t("game.decline")}
+                    </button>
+                </div>
+            },
+            Some(requester) if own_seat.as_ref() == Some(requester) => {
+                html! {<div class="smallblock">{t("game.takeback_waiting")}</div>}
             }
+            _ => html! {},
         };
 
         let reset_click = ctx.link().callback(|_| ConnectMsg::Reset);
+        let resign_click = ctx.link().callback(|_| ConnectMsg::Resign);
+        let takeback_click = ctx.link().callback(|_| ConnectMsg::RequestTakeback);
         let column_callbacks = (0..game_data.board.width)
             .map(|colnr| {
                 ctx.link().callback(move |_| {
                     log::info!("Triggered column {}", colnr);
-                    ConnectMsg::ColumnClick(colnr as usize)
+                    ConnectMsg::Play(Move::Drop(colnr as usize))
                 })
             })
             .collect::<Vec<_>>();
+        let on_roll = game_data
+            .variants
+            .roll_moves
+            .then(|| ctx.link().callback(ConnectMsg::Play));
+        let pop_callbacks = game_data.variants.pop_out.then(|| {
+            (0..game_data.board.width)
+                .map(|colnr| {
+                    ctx.link()
+                        .callback(move |_| ConnectMsg::Play(Move::Pop(colnr as usize)))
+                })
+                .collect::<Vec<_>>()
+        });
+        let push_callbacks = game_data.variants.wrap_gravity.then(|| {
+            (0..game_data.board.width)
+                .map(|colnr| {
+                    ctx.link()
+                        .callback(move |_| ConnectMsg::Play(Move::DropFromBottom(colnr as usize)))
+                })
+                .collect::<Vec<_>>()
+        });
+        // only preview moves the viewer can make
+        let preview_player = own_seat
+            .clone()
+            .filter(|player| *player == game_data.turn_player && game_data.win_status.is_none());
         html! { <>
             // <rect class="frame"/>
 
+            if self.fetch_game_data == FetchGameData::Offline {
+                <OfflineNotice/>
+            }
+            <div class="smallblock"><GamePlayers player_ids={game_data.player_ids.clone()}/></div>
             {status_html}
+            {takeback_html}
             <div class="frame">
-            <div class="grid">
-
-            <BoardView board={game_data.board.clone()} winning_chips={game_data.winning_chips.clone()} column_callbacks={column_callbacks}/>
+            <BoardView board={game_data.board.clone()} winning_chips={game_data.winning_chips.clone()} column_callbacks={column_callbacks} pop_callbacks={pop_callbacks} push_callbacks={push_callbacks} on_roll={on_roll}
+                turn_player={preview_player} win_length={game_data.win_length as usize}
+                variants={game_data.variants}
+                moves={game_data.moves.clone()}/>
             // TODO: cloning isn't optimal. Possible solution: make board and winning_chips fields Rc<_> to allow sharing a reference
             // to the props
             </div>
-            </div>
-            <button onclick={reset_click} class="smallblock">{"Reset"}</button>
+            if own_seat.is_some() && game_data.is_over() {
+                <button onclick={reset_click} class="smallblock">{t("game.reset")}</button>
+            }
+            if can_resign {
+                <button onclick={resign_click} class="smallblock">{t("game.resign")}</button>
+            }
+            if can_request_takeback {
+                <button onclick={takeback_click} class="smallblock">{t("game.take_back")}</button>
+            }
+            <ChatPanel game_id={ctx.props().game_id} seated={own_seat.is_some()}/>
             // <DumbGet />
             </>
         }
@@ -223,7 +262,7 @@ impl Component for ConnectGame {
                 }
             }
 
-            ConnectMsg::ColumnClick(colnr) => {
+            ConnectMsg::Play(mv) => {
                 let id = get_player_id();
 
                 let mut game_data = match &self.fetch_game_data {
@@ -249,70 +288,48 @@ impl Component for ConnectGame {
                     return false;
                 }
 
-                let insert = game_data
-                    .board
-                    .insert(colnr, &game_data.turn_player.clone());
-                // TODO: above we need to clone since we borrow self mutably for insert. Nice way without clone?
-                match insert {
-                    Ok(row) => {
-                        if game_data.board.check_win(
-                            colnr,
-                            row,
-                            &game_data.turn_player,
-                            game_data.win_length as usize,
-                        ) {
-                            game_data.win_status = Some(game_data.turn_player.clone());
-                            game_data.winning_chips = Some(game_data.board.find_winning_chips(
-                                colnr,
-                                row,
-                                &game_data.turn_player,
-                                game_data.win_length as usize,
-                            ));
-                            // log::info!("{:?}", self.winning_chips)
-                        }
-                        game_data.next_turn();
-                        spawn_local(async move {
-                            Request::post("/api/save_game")
-                                .body(serde_json::to_string(&game_data).unwrap())
-                                .send()
-                                .await
-                                .unwrap();
-                        });
-                        ctx.link()
-                            .send_message(ConnectMsg::SetFetchState(FetchGameData::NotFetching));
-                        // make view method fetch again
-                    }
-                    Err(_) => (), // do not switch turn, invalid move
+                // if the move fails, do not switch turn, invalid move
+                if game_data.play_move(mv).is_ok() {
+                    Self::save(ctx, game_data);
                 }
             }
             ConnectMsg::Reset => {
-                let new_game_data = GameData::new(
-                    7,
-                    6,
-                    4,
-                    self.game_data_cache.game_id,
-                    self.game_data_cache.player1_id,
-                    self.game_data_cache.player2_id,
-                );
-                self.game_data_cache = new_game_data.clone();
-
-                spawn_local(async move {
-                    Request::post("/api/save_game")
-                        .body(serde_json::to_string(&new_game_data).unwrap())
-                        .send()
-                        .await
-                        .unwrap();
+                self.reset();
+                Self::save(ctx, self.game_data_cache.clone());
+            }
+            ConnectMsg::Resign => {
+                let (mut game_data, player) = match self.own_seat() {
+                    Some(seat) => seat,
+                    None => return false,
+                };
+                game_data.resign(player);
+                Self::save(ctx, game_data);
+            }
+            // the server checks the seats and changes the stored game, the new state is fetched after
+            ConnectMsg::RequestTakeback => {
+                let game_id = ctx.props().game_id;
+                ctx.link().send_future(async move {
+                    if let Err(err) = request_takeback(game_id).await {
+                        log::info!("Failed to request a takeback: {}", err);
+                    }
+                    ConnectMsg::SetFetchState(FetchGameData::NotFetching)
+                });
+            }
+            ConnectMsg::AnswerTakeback(accept) => {
+                let game_id = ctx.props().game_id;
+                ctx.link().send_future(async move {
+                    if let Err(err) = answer_takeback(game_id, accept).await {
+                        log::info!("Failed to answer the takeback request: {}", err);
+                    }
+                    ConnectMsg::SetFetchState(FetchGameData::NotFetching)
                 });
-
-                ctx.link()
-                    .send_message(ConnectMsg::SetFetchState(FetchGameData::NotFetching));
             }
             ConnectMsg::GetData => {
                 use ConnectMsg::SetFetchState;
                 ctx.link()
                     .send_message(SetFetchState(FetchGameData::Fetching));
 
-                let game_id = ctx.props().game_id.clone();
+                let game_id = ctx.props().game_id;
                 ctx.link().send_future(async move {
                     match get_object(&format!("/api/gamedata/{}", game_id)).await {
                         //TODO maybe weird to get game id from props instead of GameData, but it is easiest
@@ -320,6 +337,7 @@ impl Component for ConnectGame {
                             Some(gamedata) => SetFetchState(FetchGameData::Success(gamedata)),
                             None => SetFetchState(FetchGameData::InvalidId),
                         },
+                        Err(_) if is_offline() => SetFetchState(FetchGameData::Offline),
                         Err(_) => SetFetchState(FetchGameData::Failed),
                     }
                 });
@@ -1,4 +1,4 @@
-use crate::IdType;
+use common::IdType;
 
 pub fn get_player_id() -> IdType {
     #[cfg(target_arch = "wasm32")] // TODO fix rust analyzer
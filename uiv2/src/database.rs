@@ -1,6 +1,8 @@
-use crate::gamelist::GameList;
+use common::chat::{ChatResult, NewChatMessage};
+use common::lobby::{JoinResult, LobbyAction, LobbyActionResult};
+use common::IdType;
 use reqwasm::http::Request;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
 
 // pub async fn get_gamedata() -> Result<GameData, anyhow::Error> {
 //     // consider making a Client in yew and passing it in here to prevent reopening channels
@@ -42,7 +44,7 @@ where
         log::info!("Failed to deserialize JSON: {}", &object_json);
         "Failed to deserialize JSON".to_owned()
     })?;
-    return Ok(gamelist);
+    Ok(gamelist)
 }
 
 pub async fn post_object<T>(url: &str, object: T) -> Result<(), String>
@@ -54,10 +56,61 @@ where
         .body(object_json)
         .send()
         .await
-        .map_err(|_| "Post request failed");
+        .map_err(|_| "Post request failed")?;
     Ok(())
 }
 
-pub async fn join_game(game_id: usize) {
-    Request::get(&format!("joingame/{}", game_id));
+async fn post_for_object<T>(url: &str) -> Result<T, String>
+where
+    T: DeserializeOwned,
+{
+    response_object(Request::post(url)).await
+}
+
+/// Sends `request` and parses the json it gets back
+async fn response_object<T>(request: Request) -> Result<T, String>
+where
+    T: DeserializeOwned,
+{
+    let result_json = request
+        .send()
+        .await
+        .map_err(|_| "Post request failed".to_owned())?
+        .text()
+        .await
+        .map_err(|_| "Failed to get response body".to_owned())?;
+
+    serde_json::from_str(&result_json).map_err(|_| {
+        log::info!("Failed to deserialize JSON: {}", &result_json);
+        "Failed to deserialize JSON".to_owned()
+    })
+}
+
+pub async fn join_game(game_id: IdType) -> Result<JoinResult, String> {
+    post_for_object(&format!("/api/join/{}", game_id)).await
+}
+
+/// Asks the other players to take back this player's last move, the result says if the request was made
+pub async fn request_takeback(game_id: IdType) -> Result<bool, String> {
+    post_for_object(&format!("/api/takeback/{}", game_id)).await
+}
+
+/// Answers the pending takeback request, the result says if the answer was taken
+pub async fn answer_takeback(game_id: IdType, accept: bool) -> Result<bool, String> {
+    post_for_object(&format!("/api/answer_takeback/{}/{}", game_id, accept)).await
+}
+
+pub async fn send_chat_message(
+    game_id: IdType,
+    message: &NewChatMessage,
+) -> Result<ChatResult, String> {
+    let message_json = serde_json::to_string(message).map_err(|_| "Serializing failed")?;
+    response_object(Request::post(&format!("/api/chat/{}", game_id)).body(message_json)).await
+}
+
+pub async fn lobby_action(
+    action: LobbyAction,
+    game_id: IdType,
+) -> Result<LobbyActionResult, String> {
+    post_for_object(&format!("/api/{}", action.route(game_id))).await
 }
@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+use yew::prelude::*;
+
+/// A drag with the mouse or a finger in progress: where it started and the state at that point
+struct Drag<T> {
+    start: (i32, i32),
+    state: T,
+    moved: bool,
+}
+
+/// Pointer handlers for a view that is dragged around, to put on its svg element
+pub struct DragHandlers {
+    pub onpointerdown: Callback<PointerEvent>,
+    pub onpointermove: Callback<PointerEvent>,
+    pub onpointerup: Callback<PointerEvent>,
+    /// Also for `onpointercancel`, it ends a touch drag the browser took over
+    pub onpointerleave: Callback<PointerEvent>,
+    /// Set when a drag ends, so the click that comes with the mouseup can be ignored. Clicks
+    /// should check it with `replace(false)`.
+    pub dragged: Rc<RefCell<bool>>,
+}
+
+/// Updates `state` while the pointer is dragged over the element. `drag_to` gets the state when
+/// the drag started, the pixels dragged so far and the element, and returns the new state, or
+/// none while the drag is still small enough to count as a click.
+#[hook]
+pub fn use_drag<T, F>(state: UseStateHandle<T>, drag_to: F) -> DragHandlers
+where
+    T: Clone + PartialEq + 'static,
+    F: Fn(&T, (i32, i32), &Element) -> Option<T> + 'static,
+{
+    let drag = use_mut_ref(|| None::<Drag<T>>);
+    let dragged = use_mut_ref(|| false);
+
+    let onpointerdown = {
+        let drag = drag.clone();
+        let state = state.clone();
+        Callback::from(move |e: PointerEvent| {
+            *drag.borrow_mut() = Some(Drag {
+                start: (e.client_x(), e.client_y()),
+                state: (*state).clone(),
+                moved: false,
+            });
+        })
+    };
+    let onpointermove = {
+        let drag = drag.clone();
+        Callback::from(move |e: PointerEvent| {
+            let mut drag = drag.borrow_mut();
+            let Some(drag) = drag.as_mut() else {
+                return;
+            };
+            let Some(element) = e
+                .current_target()
+                .and_then(|t| t.dyn_into::<Element>().ok())
+            else {
+                return;
+            };
+            let pixels = (e.client_x() - drag.start.0, e.client_y() - drag.start.1);
+            let new_state = drag_to(&drag.state, pixels, &element);
+            drag.moved |= new_state.is_some();
+            let new_state = new_state.unwrap_or_else(|| drag.state.clone());
+            if new_state != *state {
+                state.set(new_state);
+            }
+        })
+    };
+    let onpointerup = {
+        let drag = drag.clone();
+        let dragged = dragged.clone();
+        Callback::from(move |_: PointerEvent| {
+            if let Some(drag) = drag.borrow_mut().take() {
+                *dragged.borrow_mut() = drag.moved;
+            }
+        })
+    };
+    let onpointerleave = Callback::from(move |_: PointerEvent| {
+        drag.borrow_mut().take();
+    });
+
+    DragHandlers {
+        onpointerdown,
+        onpointermove,
+        onpointerup,
+        onpointerleave,
+        dragged,
+    }
+}
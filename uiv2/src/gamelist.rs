@@ -1,23 +1,23 @@
-use serde::{Deserialize, Serialize};
+use common::lobby::{
+    GameList, GameLobby, JoinResult, LobbyFilter, LobbyPage, LobbySettings, LobbySort, TimeControl,
+};
 use yew_router::prelude::use_navigator;
 // use surf;
 use crate::cookies::get_player_id;
-use crate::IdType;
-use crate::{database::get_object, Pages};
-use reqwasm::http::Request;
+use crate::i18n::{join_result_text, lobby_sort_text, t, tf, time_control_text, tn};
+use crate::offline::{is_offline, OfflineNotice};
+use crate::profile::PlayerName;
+use crate::{
+    database::{get_object, join_game},
+    Pages,
+};
+use gloo_timers::callback::{Interval, Timeout};
+use std::ops::RangeInclusive;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
+use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
-#[derive(PartialEq, Properties, Clone, Serialize, Deserialize, Debug)]
-pub struct GameLobby {
-    pub game_id: IdType,
-    pub player1_id: Option<IdType>,
-    pub player2_id: Option<IdType>,
-    pub game_name: String, // TODO: yew recommends using their AttrValue instead
-    // password: String
-    pub game_started: bool,
-}
-
 #[derive(PartialEq, Clone, Copy)]
 pub enum LobbyMode {
     Join,
@@ -28,19 +28,9 @@ pub enum LobbyMode {
 pub struct GameLobbyBlockProps {
     gamelobby: GameLobby,
     mode: LobbyMode,
-}
-
-fn option_to_binary<T>(x: Option<T>) -> i32 {
-    match x {
-        Some(_) => 1,
-        None => 0,
-    }
-}
-
-impl GameLobby {
-    pub fn number_players_joined(&self) -> i32 {
-        option_to_binary(self.player1_id) + option_to_binary(self.player2_id)
-    }
+    /// Shown next to the host's name, only known for lobbies the player can join
+    #[prop_or_default]
+    host_rating: Option<i32>,
 }
 
 #[function_component(GameLobbyBlock)]
@@ -49,41 +39,57 @@ fn game_lobby_block(props: &GameLobbyBlockProps) -> Html {
     let game_id = props.gamelobby.game_id;
     let navigator = use_navigator().unwrap();
     let mode = props.mode;
-    let onclick = Callback::from(move |_| {
-        let navigator = navigator.clone();
-        spawn_local(async move {
-            if mode == Join {
-                Request::get(&format!("/api/join/{}", game_id))
-                    .send()
-                    .await
-                    .unwrap();
-            }
-            navigator.push(&Pages::Lobby {
-                game_id: game_id.to_string(),
+    let join_error = use_state(|| None::<JoinResult>);
+    let onclick = {
+        let join_error = join_error.clone();
+        Callback::from(move |_| {
+            let navigator = navigator.clone();
+            let join_error = join_error.clone();
+            spawn_local(async move {
+                if mode == Join {
+                    let join_result = join_game(game_id).await.unwrap_or(JoinResult::Failed);
+                    if !join_result.is_seated() {
+                        join_error.set(Some(join_result));
+                        return;
+                    }
+                }
+                navigator.push(&Pages::Lobby {
+                    game_id: game_id.to_string(),
+                });
             });
-        });
-    });
+        })
+    };
     html! {
         <div class="gamelobby"> // TODO add class
             {&props.gamelobby.game_name}
-            {format!("\n{}/2", props.gamelobby.number_players_joined())}
+            if let Some(host_id) = props.gamelobby.host_id() {
+                <PlayerName player_id={host_id}/>
+            }
+            if let Some(rating) = props.host_rating {
+                {" "}{tf("list.host_rating", &[&rating])}
+            }
+            {"\n"}
+            {tn(
+                "players_joined",
+                props.gamelobby.number_players_joined(),
+                &[&props.gamelobby.settings.num_players],
+            )}
+            {", "}{time_control_text(props.gamelobby.settings.time_control)}
             // <form action="/api/join" method="post">
             //     <input type="hidden" name="game_id" value={gamelobby.game_id.to_string()}/>
             //     <input class="join" type="submit" value="Submit"/>
             // </form>
             <button class="greenbutton" onclick={onclick}> {match mode {
-                Join => "Join!",
-                Open => "Open"
+                Join => t("list.join"),
+                Open => t("list.open"),
             }} </button>
+            if let Some(join_result) = *join_error {
+                <p>{join_result_text(&join_result)}</p>
+            }
         </div>
     }
 }
 
-#[derive(PartialEq, Properties, Serialize, Deserialize, Debug)]
-pub struct GameList {
-    pub games: Vec<GameLobby>,
-}
-
 // #[function_component(GameListView)]
 // pub fn game_list_view(gamelist: &GameList) -> Html {
 // gamelist
@@ -93,32 +99,195 @@ pub struct GameList {
 //     .collect::<Html>()
 // }
 
+/// Milliseconds between refreshes of the list, which keep the open games count up to date
+const REFRESH_INTERVAL: u32 = 5000;
+
 #[derive(PartialEq)]
 pub enum FetchGameList {
     NotFetching,
     Fetching,
-    Success((GameList, GameList)),
+    /// The lobbies the player is in and a page of the lobbies they can join
+    Success((GameList, LobbyPage)),
     Failed,
+    /// The browser has no connection, the list is fetched again in a moment
+    Offline,
 }
 
 // #[derive(PartialEq)]
 pub enum GameListMsg {
     SetFetchState(FetchGameList),
+    /// Answer to a fetch with the given filter, dropped if the filter changed in the meantime
+    Fetched(LobbyFilter, FetchGameList),
     GetData,
+    SetFilter(LobbyFilter),
 }
 
-#[derive(PartialEq, Properties)]
 pub struct GameListView {
     fetch_state: FetchGameList,
+    filter: LobbyFilter,
+    _refresh: Interval,
+}
+
+/// Value of the input or select an event came from
+fn event_value(e: &Event) -> Option<String> {
+    let target: EventTarget = e.target()?;
+    match target.dyn_into::<HtmlInputElement>() {
+        Ok(input) => Some(input.value()),
+        Err(target) => target
+            .dyn_into::<HtmlSelectElement>()
+            .ok()
+            .map(|select| select.value()),
+    }
+}
+
+impl GameListView {
+    /// Callback applying `change` with the new value of an input to the filter, starting over at
+    /// the first page
+    fn filter_callback(
+        &self,
+        ctx: &Context<Self>,
+        change: impl Fn(&mut LobbyFilter, String) + 'static,
+    ) -> Callback<Event> {
+        let filter = self.filter.clone();
+        ctx.link().batch_callback(move |e: Event| {
+            let mut filter = filter.clone();
+            change(&mut filter, event_value(&e)?);
+            filter.page = 0;
+            Some(GameListMsg::SetFilter(filter))
+        })
+    }
+
+    /// Select for the board size or win length, `None` matches any
+    fn size_select(
+        &self,
+        ctx: &Context<Self>,
+        label: &str,
+        sizes: RangeInclusive<u8>,
+        field: fn(&mut LobbyFilter) -> &mut Option<u8>,
+    ) -> Html {
+        let value = *field(&mut self.filter.clone());
+        let onchange = self.filter_callback(ctx, move |filter, value| {
+            *field(filter) = value.parse().ok();
+        });
+        html! {
+            <label>{label}{" "}
+                <select {onchange}>
+                    <option value="" selected={value.is_none()}>{t("list.any")}</option>
+                    {sizes.map(|size| html! {
+                        <option value={size.to_string()} selected={value == Some(size)}>{size}</option>
+                    }).collect::<Html>()}
+                </select>
+            </label>
+        }
+    }
+
+    /// Number input for one end of the host rating range, empty matches any
+    fn rating_input(
+        &self,
+        ctx: &Context<Self>,
+        label: &str,
+        field: fn(&mut LobbyFilter) -> &mut Option<i32>,
+    ) -> Html {
+        let value = field(&mut self.filter.clone()).map(|rating| rating.to_string());
+        let onchange = self.filter_callback(ctx, move |filter, value| {
+            *field(filter) = value.trim().parse().ok();
+        });
+        html! {
+            <label>{label}{" "}
+                <input type="number" step="50" value={value.unwrap_or_default()} {onchange}/>
+            </label>
+        }
+    }
+
+    fn filter_html(&self, ctx: &Context<Self>) -> Html {
+        let on_search = self.filter_callback(ctx, |filter, value| {
+            filter.search = value.trim().to_owned();
+        });
+        let on_time_control = self.filter_callback(ctx, |filter, value| {
+            filter.time_control = value.parse::<u8>().ok().and_then(|n| n.try_into().ok());
+        });
+        let on_sort = self.filter_callback(ctx, |filter, value| {
+            if let Some(sort) = value.parse::<u8>().ok().and_then(|n| n.try_into().ok()) {
+                filter.sort = sort;
+            }
+        });
+        let clear = ctx
+            .link()
+            .callback(|_| GameListMsg::SetFilter(LobbyFilter::default()));
+        let max_size = LobbySettings::MAX_SIZE;
+        html! {
+            <div class="settings lobbyfilter">
+                <label>{t("list.search")}{" "}
+                    <input type="search" placeholder={t("list.search_placeholder")}
+                        value={self.filter.search.clone()} onchange={on_search}/>
+                </label>
+                {self.size_select(ctx, &t("home.width"), LobbySettings::MIN_SIZE..=max_size, |filter| &mut filter.width)}
+                {self.size_select(ctx, &t("home.height"), LobbySettings::MIN_SIZE..=max_size, |filter| &mut filter.height)}
+                {self.size_select(ctx, &t("home.connect"), LobbySettings::MIN_WIN_LENGTH..=max_size, |filter| &mut filter.win_length)}
+                <label>{t("list.time_control")}{" "}
+                    <select onchange={on_time_control}>
+                        <option value="" selected={self.filter.time_control.is_none()}>{t("list.any")}</option>
+                        {TimeControl::ALL.iter().map(|time_control| html! {
+                            <option value={u8::from(*time_control).to_string()}
+                            selected={Some(*time_control) == self.filter.time_control}>
+                                {time_control_text(*time_control)}
+                            </option>
+                        }).collect::<Html>()}
+                    </select>
+                </label>
+                {self.rating_input(ctx, &t("list.min_rating"), |filter| &mut filter.min_rating)}
+                {self.rating_input(ctx, &t("list.max_rating"), |filter| &mut filter.max_rating)}
+                <label>{t("list.sort")}{" "}
+                    <select onchange={on_sort}>
+                        {LobbySort::ALL.iter().map(|sort| html! {
+                            <option value={u8::from(*sort).to_string()} selected={*sort == self.filter.sort}>
+                                {lobby_sort_text(*sort)}
+                            </option>
+                        }).collect::<Html>()}
+                    </select>
+                </label>
+                <button onclick={clear}>{t("list.clear_filters")}</button>
+            </div>
+        }
+    }
+
+    fn pagination_html(&self, ctx: &Context<Self>, lobby_page: &LobbyPage) -> Html {
+        let num_pages = lobby_page.num_pages();
+        let page = self.filter.page.min(num_pages - 1);
+        let to_page = |page: u32| {
+            let filter = LobbyFilter {
+                page,
+                ..self.filter.clone()
+            };
+            ctx.link()
+                .callback(move |_| GameListMsg::SetFilter(filter.clone()))
+        };
+        html! {
+            <div class="pagination">
+                <button disabled={page == 0} onclick={to_page(page.saturating_sub(1))}>
+                    {t("list.previous")}
+                </button>
+                {tf("list.page", &[&(page + 1), &num_pages])}
+                <button disabled={page + 1 >= num_pages} onclick={to_page(page + 1)}>
+                    {t("list.next")}
+                </button>
+            </div>
+        }
+    }
 }
 
 impl Component for GameListView {
     type Message = GameListMsg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
         Self {
             fetch_state: FetchGameList::NotFetching,
+            filter: LobbyFilter::default(),
+            _refresh: Interval::new(REFRESH_INTERVAL, move || {
+                link.send_message(GameListMsg::GetData)
+            }),
         }
     }
 
@@ -129,31 +298,42 @@ impl Component for GameListView {
         }
 
         match &self.fetch_state {
-            NotFetching => html! {"Please be patient"},
-            Fetching => html! {"fetching open games"},
-            Success((joined_gamelist, joinable_gamelist)) => html! {
+            NotFetching => html! {t("list.patience")},
+            Fetching => html! {t("list.fetching")},
+            Success((joined_gamelist, lobby_page)) => html! {
             <>
-            if joined_gamelist.games.len() > 0 {
-                <h2>{"Continue playing"}</h2>
+            if !joined_gamelist.games.is_empty() {
+                <h2>{t("list.continue")}</h2>
                 {joined_gamelist
                 .games
                 .iter()
-                .map(|game| html! {<GameLobbyBlock gamelobby={game.clone()} mode={LobbyMode::Open}/>})
+                .map(|game| html! {<GameLobbyBlock key={game.game_id} gamelobby={game.clone()} mode={LobbyMode::Open}/>})
                 .collect::<Html>()}
             }
 
-            <h2>{"Join a game"}</h2>
-            if joinable_gamelist.games.len() > 0 {
-                {joinable_gamelist
+            <h2>{t("list.join_a_game")}</h2>
+            <p aria-live="polite">{tn("list.open_games", lobby_page.open as usize, &[])}</p>
+            {self.filter_html(ctx)}
+            if !lobby_page.games.is_empty() {
+                {lobby_page
                     .games
                     .iter()
-                    .map(|game| html! {<GameLobbyBlock gamelobby={game.clone()} mode={LobbyMode::Join}/>})
-                    .collect::<Html>()}}
+                    .map(|game| html! {
+                        <GameLobbyBlock key={game.lobby.game_id} gamelobby={game.lobby.clone()}
+                            host_rating={game.host_rating} mode={LobbyMode::Join}/>
+                    })
+                    .collect::<Html>()}
+                {self.pagination_html(ctx, lobby_page)}
+            }
+            else if self.filter != LobbyFilter::default() {
+                <p> {t("list.no_matches")} </p>
+            }
             else {
-                <p> {"There aren't any open games. Go to the homepage to create a new one!"} </p>
+                <p> {t("list.no_games")} </p>
             }
             </>},
-            Failed => html! {"Failed to get the data. Please refresh to try again"},
+            Failed => html! {t("list.failed")},
+            Offline => html! {<OfflineNotice/>},
         }
     }
 
@@ -161,13 +341,43 @@ impl Component for GameListView {
         use GameListMsg::*;
         match msg {
             SetFetchState(state) => {
+                if state == FetchGameList::Offline {
+                    let retry = ctx
+                        .link()
+                        .callback(|_| SetFetchState(FetchGameList::NotFetching));
+                    Timeout::new(2000, move || retry.emit(())).forget();
+                }
                 self.fetch_state = state;
                 true
             }
+            Fetched(filter, state) => {
+                if filter != self.filter {
+                    return false;
+                }
+                // lobbies filled up or closed since the page was picked, go back to the last one left
+                if let FetchGameList::Success((_, lobby_page)) = &state {
+                    if lobby_page.games.is_empty() && filter.page >= lobby_page.num_pages() {
+                        ctx.link().send_message(SetFilter(LobbyFilter {
+                            page: lobby_page.num_pages() - 1,
+                            ..filter
+                        }));
+                        return false;
+                    }
+                }
+                Component::update(self, ctx, SetFetchState(state))
+            }
+            SetFilter(filter) => {
+                self.filter = filter;
+                ctx.link().send_message(GetData);
+                true
+            }
             GetData => {
-                ctx.link()
-                    .send_message(SetFetchState(FetchGameList::Fetching));
-                ctx.link().send_future(async {
+                // refreshes and filter changes keep the list on screen until the new one is in
+                if !matches!(self.fetch_state, FetchGameList::Success(_)) {
+                    self.fetch_state = FetchGameList::Fetching;
+                }
+                let filter = self.filter.clone();
+                ctx.link().send_future(async move {
                     // surf implementation
                     // match surf::get("/gamelistdata").recv_json::<GameList>().await {
                     //     Ok(gamelist) => return SetFetchState(FetchGameList::Success(gamelist)),
@@ -183,20 +393,31 @@ impl Component for GameListView {
                     //     .unwrap();
 
                     // let gamelist = serde_json::from_str(&gamelist_json).unwrap();
+                    let failed = |filter| match is_offline() {
+                        true => Fetched(filter, FetchGameList::Offline),
+                        false => Fetched(filter, FetchGameList::Failed),
+                    };
                     let player_id = get_player_id();
                     let joined_gamelist: GameList =
                         match get_object(&format!("/api/get_joined_lobbies/{}", player_id)).await {
                             Ok(gamelist) => gamelist,
-                            Err(_) => return SetFetchState(FetchGameList::Failed),
+                            Err(_) => return failed(filter),
                         };
 
-                    let joinable_gamelist =
-                        match get_object(&format!("/api/get_joinable_lobbies/{}", player_id)).await
-                        {
-                            Ok(gamelist) => gamelist,
-                            Err(_) => return SetFetchState(FetchGameList::Failed),
-                        };
-                    SetFetchState(FetchGameList::Success((joined_gamelist, joinable_gamelist)))
+                    let lobby_page = match get_object(&format!(
+                        "/api/get_joinable_lobbies/{}?{}",
+                        player_id,
+                        filter.query_string()
+                    ))
+                    .await
+                    {
+                        Ok(lobby_page) => lobby_page,
+                        Err(_) => return failed(filter),
+                    };
+                    Fetched(
+                        filter,
+                        FetchGameList::Success((joined_gamelist, lobby_page)),
+                    )
                 });
                 true
             }
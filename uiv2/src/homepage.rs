@@ -1,11 +1,76 @@
 use reqwasm::http::Request;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{EventTarget, HtmlInputElement};
+use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
+use crate::i18n::{first_player_text, t, time_control_text, tn};
+use crate::localconnectgame::{delete_saved_game, saved_games};
+use crate::offline::is_offline;
 use crate::Pages;
+use common::lobby::{FirstPlayer, LobbySettings, NewLobby, TimeControl};
+use common::rules::Variants;
+use common::Player;
+use std::ops::RangeInclusive;
+
+/// Number input for one of the lobby settings, `field` picks which one and `range` the values
+/// `LobbySettings::is_valid` accepts for it
+fn setting_input(
+    label: &str,
+    settings_handle: &UseStateHandle<LobbySettings>,
+    range: RangeInclusive<u8>,
+    field: fn(&mut LobbySettings) -> &mut u8,
+) -> Html {
+    let value = field(&mut (**settings_handle).clone()).to_string();
+    let on_change = {
+        let settings_handle = settings_handle.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let input = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
+
+            if let Some(value) = input.and_then(|input| input.value().parse().ok()) {
+                let mut settings = (*settings_handle).clone();
+                *field(&mut settings) = value;
+                settings_handle.set(settings);
+            }
+        })
+    };
+    html! {
+        <label>{label}
+            <input type="number" min={range.start().to_string()}
+            max={range.end().to_string()} {value} onchange={on_change}/>
+        </label>
+    }
+}
+
+/// Checkbox turning one of the rule variants on or off
+fn variant_checkbox(
+    label: &str,
+    settings_handle: &UseStateHandle<LobbySettings>,
+    field: fn(&mut Variants) -> &mut bool,
+) -> Html {
+    let checked = *field(&mut settings_handle.variants.clone());
+    let on_change = {
+        let settings_handle = settings_handle.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let input = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
+
+            if let Some(input) = input {
+                let mut settings = (*settings_handle).clone();
+                *field(&mut settings.variants) = input.checked();
+                settings_handle.set(settings);
+            }
+        })
+    };
+    html! {
+        <label>
+            <input type="checkbox" {checked} onchange={on_change}/>
+            {label}
+        </label>
+    }
+}
 
 #[function_component]
 pub fn HomePage() -> Html {
@@ -25,21 +90,90 @@ pub fn HomePage() -> Html {
         })
     };
 
+    let settings_handle = use_state(LobbySettings::default);
+    let on_first_player_change = {
+        let settings_handle = settings_handle.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+
+            if let Some(first_player) = select
+                .and_then(|select| select.value().parse::<u8>().ok())
+                .and_then(|num| FirstPlayer::try_from(num).ok())
+            {
+                settings_handle.set(LobbySettings {
+                    first_player,
+                    ..(*settings_handle).clone()
+                });
+            }
+        })
+    };
+    let on_time_control_change = {
+        let settings_handle = settings_handle.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+
+            if let Some(time_control) = select
+                .and_then(|select| select.value().parse::<u8>().ok())
+                .and_then(|num| TimeControl::try_from(num).ok())
+            {
+                settings_handle.set(LobbySettings {
+                    time_control,
+                    ..(*settings_handle).clone()
+                });
+            }
+        })
+    };
+    let on_num_players_change = {
+        let settings_handle = settings_handle.clone();
+        Callback::from(move |e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+
+            if let Some(num_players) = select.and_then(|select| select.value().parse().ok()) {
+                settings_handle.set(LobbySettings {
+                    num_players,
+                    ..(*settings_handle).clone()
+                });
+            }
+        })
+    };
+    let create_error = use_state(|| None::<String>);
+
     let navigator = use_navigator().unwrap();
     let input_value_clone = input_value.clone();
+    let settings = (*settings_handle).clone();
+    let create_error_clone = create_error.clone();
     let create_game = move || {
-        let input_value_clone = input_value_clone.clone();
+        let new_lobby = NewLobby {
+            game_name: input_value_clone.clone(),
+            settings: settings.clone(),
+        };
         let navigator = navigator.clone();
+        let create_error = create_error_clone.clone();
         log::info!("{}", input_value_clone);
+        if !new_lobby.settings.is_valid() {
+            create_error.set(Some(t("home.invalid_settings")));
+            return;
+        }
         spawn_local(async move {
-            let game_id = Request::post("/api/create_game_lobby")
-                .body(input_value_clone)
+            let response = match Request::post("/api/create_game_lobby")
+                .body(serde_json::to_string(&new_lobby).unwrap())
                 .send()
                 .await
-                .unwrap()
-                .text()
-                .await
-                .unwrap();
+            {
+                Ok(response) if response.ok() => response,
+                _ if is_offline() => {
+                    create_error.set(Some(t("home.offline")));
+                    return;
+                }
+                _ => {
+                    create_error.set(Some(t("home.create_failed")));
+                    return;
+                }
+            };
+            let game_id = response.text().await.unwrap();
 
             log::info!("Game_id on front end: {}", &game_id);
             // let game_id: u64 = game_id.parse().unwrap();
@@ -80,12 +214,65 @@ pub fn HomePage() -> Html {
     //TODO: either make input a form to call submit on enter, or remove the on_submit_input stuff
 
     let navigator = use_navigator().unwrap();
-    let to_local_game = Callback::from(move |_| navigator.push(&Pages::Local));
+    let to_local_game = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Pages::Local))
+    };
+    let to_settings = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Pages::Settings))
+    };
+    let to_profile = {
+        let navigator = navigator.clone();
+        Callback::from(move |_| navigator.push(&Pages::OwnProfile))
+    };
+    // names of the saved local games, the unnamed one is resumed by "Play local game"
+    let saved_names = use_state(|| {
+        saved_games()
+            .into_keys()
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let saved_games_html = saved_names
+        .iter()
+        .map(|name| {
+            let resume = {
+                let navigator = navigator.clone();
+                let name = name.clone();
+                Callback::from(move |_| navigator.push(&Pages::SavedLocal { name: name.clone() }))
+            };
+            let delete = {
+                let saved_names = saved_names.clone();
+                let name = name.clone();
+                Callback::from(move |_| {
+                    delete_saved_game(&name);
+                    saved_names.set(
+                        saved_names
+                            .iter()
+                            .filter(|n| **n != name)
+                            .cloned()
+                            .collect(),
+                    );
+                })
+            };
+            html! {
+                <div class="gamelobby">
+                    {name}
+                    <button class="greenbutton" onclick={resume}>{t("home.resume")}</button>
+                    <button class="greenbutton" onclick={delete}>{t("home.delete")}</button>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    let size_range = LobbySettings::MIN_SIZE..=LobbySettings::MAX_SIZE;
+    let win_length_range =
+        LobbySettings::MIN_WIN_LENGTH..=settings_handle.width.max(settings_handle.height);
 
     html! {
         <div class="mainpage">
         <a href="/gamelist">
-            <button class="smallblock" style="cursor:pointer">{"Find game"}</button>
+            <button class="smallblock" style="cursor:pointer">{t("home.find_game")}</button>
         </a>
 
         // <form action="/api/create_game_lobby" method="post">
@@ -93,20 +280,58 @@ pub fn HomePage() -> Html {
         //     <input type="text" id="game_name" name="game_name"/>
         //     <input type="submit" value="Submit"/>
         // </form>
-        <p>{"Enter game name"}</p>
+        <p>{t("home.enter_name")}</p>
         <input onchange={on_change} onsubmit={on_submit_input}
         id="cautious-input"
         type="text"
         value={input_value.clone()}
         />
-        <button class="smallblock" style="cursor:pointer" onclick={on_submit_button}> {"Create game"} </button>
+        <div class="settings">
+            {setting_input(&t("home.width"), &settings_handle, size_range.clone(), |settings| &mut settings.width)}
+            {setting_input(&t("home.height"), &settings_handle, size_range, |settings| &mut settings.height)}
+            {setting_input(&t("home.connect"), &settings_handle, win_length_range, |settings| &mut settings.win_length)}
+            <select onchange={on_num_players_change}>
+                {(Player::MIN_PLAYERS..=Player::MAX_PLAYERS).map(|num_players| html! {
+                    <option value={num_players.to_string()}
+                    selected={num_players == settings_handle.num_players}>
+                        {tn("players", num_players as usize, &[])}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+            <select onchange={on_first_player_change}>
+                {FirstPlayer::ALL.iter().map(|first_player| html! {
+                    <option value={u8::from(*first_player).to_string()}
+                    selected={*first_player == settings_handle.first_player}>
+                        {first_player_text(*first_player)}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+            <select onchange={on_time_control_change}>
+                {TimeControl::ALL.iter().map(|time_control| html! {
+                    <option value={u8::from(*time_control).to_string()}
+                    selected={*time_control == settings_handle.time_control}>
+                        {time_control_text(*time_control)}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+            {variant_checkbox(&t("variant.pop_out"), &settings_handle, |variants| &mut variants.pop_out)}
+            {variant_checkbox(&t("variant.roll_moves"), &settings_handle, |variants| &mut variants.roll_moves)}
+            {variant_checkbox(&t("variant.wrap_gravity"), &settings_handle, |variants| &mut variants.wrap_gravity)}
+        </div>
+        <button class="smallblock" style="cursor:pointer" onclick={on_submit_button}> {t("home.create_game")} </button>
+        if let Some(error) = &*create_error {
+            <p>{error}</p>
+        }
 
 
         // <div oninput={oninput}>
         // { "hi" }
         // <input type="text" />
         // </div>
-        <button onclick={to_local_game} class="smallblock" style="cursor:pointer">{"Play local game"}</button>
+        <button onclick={to_local_game} class="smallblock" style="cursor:pointer">{t("home.local_game")}</button>
+        {saved_games_html}
+        <button onclick={to_settings} class="smallblock" style="cursor:pointer">{t("home.settings")}</button>
+        <button onclick={to_profile} class="smallblock" style="cursor:pointer">{t("home.profile")}</button>
         </div>
     }
 }
@@ -0,0 +1,983 @@
+//! Translations of the interface. Every text shown to players is looked up by key in the message
+//! catalogue of the chosen language, which is kept in the `lang` cookie.
+use common::chat::{ChatResult, MAX_MESSAGE_LENGTH, RATE_LIMIT_MESSAGES, RATE_LIMIT_SECONDS};
+use common::lobby::{FirstPlayer, JoinResult, LobbyActionResult, LobbySort, TimeControl};
+use std::fmt::Display;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlSelectElement};
+use yew::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+const COOKIE_NAME: &str = "lang";
+
+thread_local! {
+    static CURRENT: Language = Language::from_cookie();
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Dutch,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::Dutch, Language::German];
+
+    /// Code used in the cookie and the `lang` attribute of the page
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Dutch => "nl",
+            Language::German => "de",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    /// Name of the language in the language itself, for the picker
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Dutch => "Nederlands",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn catalogue(&self) -> Catalogue {
+        match self {
+            Language::English => ENGLISH,
+            Language::Dutch => DUTCH,
+            Language::German => GERMAN,
+        }
+    }
+
+    /// Plural form used for `n` things, the suffix of the catalogue key. English, Dutch and German
+    /// all use the singular for exactly one and the plural otherwise.
+    fn plural_form(&self, n: usize) -> &'static str {
+        match n {
+            1 => "one",
+            _ => "other",
+        }
+    }
+
+    /// The language picked with `Language::choose`, English if there is none. Read once per page
+    /// load, choosing another language reloads the page.
+    pub fn current() -> Self {
+        CURRENT.with(|language| *language)
+    }
+
+    fn from_cookie() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let language = wasm_cookies::get(COOKIE_NAME)
+            .and_then(Result::ok)
+            .and_then(|code| Self::from_code(&code))
+            .unwrap_or_default();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let language = Self::default();
+
+        language
+    }
+
+    /// Keeps the language in a cookie and reloads the page, so everything is shown in it
+    pub fn choose(self) {
+        #[cfg(target_arch = "wasm32")]
+        wasm_cookies::set(
+            COOKIE_NAME,
+            self.code(),
+            &wasm_cookies::CookieOptions::default()
+                .with_path("/")
+                .expires_after(std::time::Duration::from_secs(365 * 24 * 60 * 60)),
+        );
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().reload();
+        }
+    }
+
+    /// Sets the `lang` attribute of the page, so screen readers pronounce the text right
+    pub fn apply(&self) {
+        if let Some(root) = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.document_element())
+        {
+            let _ = root.set_attribute("lang", self.code());
+        }
+    }
+}
+
+fn lookup(language: Language, key: &str) -> &'static str {
+    let find = |catalogue: Catalogue| {
+        catalogue
+            .iter()
+            .find(|(entry, _)| *entry == key)
+            .map(|(_, text)| *text)
+    };
+    find(language.catalogue())
+        .or_else(|| find(ENGLISH))
+        .unwrap_or_else(|| {
+            log::warn!("Missing translation for {}", key);
+            ""
+        })
+}
+
+fn fill(text: &str, args: &[&dyn Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(text.to_owned(), |text, (index, arg)| {
+            text.replace(&format!("{{{}}}", index), &arg.to_string())
+        })
+}
+
+/// The text for `key` in the current language. Missing keys fall back to English.
+pub fn t(key: &str) -> String {
+    lookup(Language::current(), key).to_owned()
+}
+
+/// Like `t`, with `{0}`, `{1}`, ... in the text replaced by `args`
+pub fn tf(key: &str, args: &[&dyn Display]) -> String {
+    fill(lookup(Language::current(), key), args)
+}
+
+/// Like `tf`, for a text about `n` things: picks `key.one` or `key.other` by the rules of the
+/// current language. `n` itself is `{0}`, the other arguments follow it.
+pub fn tn(key: &str, n: usize, args: &[&dyn Display]) -> String {
+    let language = Language::current();
+    let key = format!("{}.{}", key, language.plural_form(n));
+    let args = [&n as &dyn Display].into_iter().chain(args.iter().copied());
+    fill(lookup(language, &key), &args.collect::<Vec<_>>())
+}
+
+pub fn first_player_text(first_player: FirstPlayer) -> String {
+    t(match first_player {
+        FirstPlayer::Host => "first_player.host",
+        FirstPlayer::Guest => "first_player.guest",
+        FirstPlayer::Random => "first_player.random",
+        FirstPlayer::Alternate => "first_player.alternate",
+    })
+}
+
+pub fn time_control_text(time_control: TimeControl) -> String {
+    t(match time_control {
+        TimeControl::Unlimited => "time_control.unlimited",
+        TimeControl::Blitz => "time_control.blitz",
+        TimeControl::Rapid => "time_control.rapid",
+        TimeControl::Correspondence => "time_control.correspondence",
+    })
+}
+
+pub fn lobby_sort_text(sort: LobbySort) -> String {
+    t(match sort {
+        LobbySort::Newest => "list.sort.newest",
+        LobbySort::Oldest => "list.sort.oldest",
+        LobbySort::HighestRating => "list.sort.highest_rating",
+        LobbySort::LowestRating => "list.sort.lowest_rating",
+    })
+}
+
+pub fn join_result_text(result: &JoinResult) -> String {
+    t(match result {
+        JoinResult::Joined => "join.joined",
+        JoinResult::AlreadyJoined => "join.already_joined",
+        JoinResult::LobbyFull => "join.lobby_full",
+        JoinResult::GameStarted => "lobby.game_started",
+        JoinResult::LobbyClosed => "lobby.closed",
+        JoinResult::LobbyNotFound => "game.not_found",
+        JoinResult::Failed => "join.failed",
+    })
+}
+
+pub fn lobby_action_text(result: &LobbyActionResult) -> String {
+    t(match result {
+        LobbyActionResult::Done => "lobby.done",
+        LobbyActionResult::NotHost => "lobby.not_host",
+        LobbyActionResult::NotInLobby => "lobby.not_in_lobby",
+        LobbyActionResult::NoGuest => "lobby.no_guest",
+        LobbyActionResult::GameStarted => "lobby.game_started",
+        LobbyActionResult::LobbyClosed => "lobby.closed",
+        LobbyActionResult::LobbyNotFound => "game.not_found",
+        LobbyActionResult::Failed => "lobby.failed_action",
+    })
+}
+
+pub fn chat_result_text(result: &ChatResult) -> String {
+    match result {
+        ChatResult::Sent => String::new(),
+        ChatResult::InvalidMessage => tf("chat.invalid", &[&MAX_MESSAGE_LENGTH]),
+        ChatResult::WrongChannel => t("chat.wrong_channel"),
+        ChatResult::RateLimited => tf(
+            "chat.rate_limited",
+            &[&RATE_LIMIT_MESSAGES, &RATE_LIMIT_SECONDS],
+        ),
+        ChatResult::LobbyNotFound => t("game.not_found"),
+        ChatResult::Failed => t("chat.failed"),
+    }
+}
+
+/// Select for the interface language, choosing one reloads the page
+#[function_component(LanguagePicker)]
+pub fn language_picker() -> Html {
+    let current = Language::current();
+    let onchange = Callback::from(|e: Event| {
+        let target: Option<EventTarget> = e.target();
+        let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+        if let Some(language) = select.and_then(|select| Language::from_code(&select.value())) {
+            language.choose();
+        }
+    });
+    html! {
+        <label class="smallblock">
+            {t("settings.language")}
+            {" "}
+            <select {onchange}>
+                {Language::ALL.iter().map(|language| html! {
+                    <option value={language.code()} selected={*language == current}>
+                        {language.name()}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+        </label>
+    }
+}
+
+type Catalogue = &'static [(&'static str, &'static str)];
+
+const ENGLISH: Catalogue = &[
+    ("title", "Connect 4 on a Torus"),
+    ("not_found.title", "404: Page not found"),
+    (
+        "not_found.text",
+        "It seems you are looking in the wrong place!",
+    ),
+    (
+        "offline.text",
+        "You are offline. This page will update once you are connected again.",
+    ),
+    ("offline.local", "Local games work without a connection."),
+    ("home.find_game", "Find game"),
+    ("home.enter_name", "Enter game name"),
+    ("home.width", "Width"),
+    ("home.height", "Height"),
+    ("home.connect", "Connect"),
+    ("home.create_game", "Create game"),
+    ("home.local_game", "Play local game"),
+    ("home.resume", "Resume"),
+    ("home.delete", "Delete"),
+    ("home.settings", "Settings"),
+    (
+        "home.invalid_settings",
+        "These board settings are not allowed",
+    ),
+    (
+        "home.offline",
+        "You are offline, online games need a connection",
+    ),
+    ("home.create_failed", "Failed to create the game"),
+    ("players.one", "{0} player"),
+    ("players.other", "{0} players"),
+    ("players_joined.one", "{0} of {1} players has joined"),
+    ("players_joined.other", "{0} of {1} players have joined"),
+    ("first_player.host", "Host starts"),
+    ("first_player.guest", "Guest starts"),
+    ("first_player.random", "Random start"),
+    ("first_player.alternate", "Alternate starts"),
+    ("time_control.unlimited", "No time limit"),
+    ("time_control.blitz", "Blitz, under a minute per move"),
+    ("time_control.rapid", "Rapid, a few minutes per move"),
+    (
+        "time_control.correspondence",
+        "Correspondence, a day per move",
+    ),
+    ("variant.pop_out", "Pop Out"),
+    ("variant.roll_moves", "Roll moves"),
+    ("variant.wrap_gravity", "Wrap gravity"),
+    ("list.patience", "Please be patient"),
+    ("list.fetching", "fetching open games"),
+    ("list.continue", "Continue playing"),
+    ("list.join_a_game", "Join a game"),
+    (
+        "list.no_games",
+        "There aren't any open games. Go to the homepage to create a new one!",
+    ),
+    (
+        "list.failed",
+        "Failed to get the data. Please refresh to try again",
+    ),
+    ("list.join", "Join!"),
+    ("list.open", "Open"),
+    ("list.open_games.one", "{0} open game"),
+    ("list.open_games.other", "{0} open games"),
+    ("list.search", "Search"),
+    ("list.search_placeholder", "Game or host name"),
+    ("list.any", "Any"),
+    ("list.min_rating", "Min. rating"),
+    ("list.max_rating", "Max. rating"),
+    ("list.time_control", "Time control"),
+    ("list.sort", "Sort by"),
+    ("list.sort.newest", "Newest first"),
+    ("list.sort.oldest", "Oldest first"),
+    ("list.sort.highest_rating", "Highest rated host"),
+    ("list.sort.lowest_rating", "Lowest rated host"),
+    ("list.clear_filters", "Clear filters"),
+    ("list.no_matches", "No open games match these filters"),
+    ("list.previous", "Previous"),
+    ("list.next", "Next"),
+    ("list.page", "Page {0} of {1}"),
+    ("list.host_rating", "rating {0}"),
+    ("join.joined", "Joined the game"),
+    ("join.already_joined", "You are already in this game"),
+    ("join.lobby_full", "This game is already full"),
+    ("join.failed", "Failed to join the game, please try again"),
+    ("lobby.board", "{0}x{1} board, connect {2}"),
+    ("lobby.closed", "This game has been closed"),
+    ("lobby.game_started", "This game has already started"),
+    ("lobby.waiting", "Waiting for opponents..."),
+    ("lobby.starting", "Starting the game..."),
+    ("lobby.kick", "Kick player {0}"),
+    ("lobby.cancel", "Cancel game"),
+    ("lobby.leave", "Leave game"),
+    (
+        "lobby.failed",
+        "Failed to get the lobby. Please refresh to try again",
+    ),
+    ("lobby.wait", "Please wait..."),
+    ("lobby.done", "Done"),
+    ("lobby.not_host", "Only the host can do this"),
+    ("lobby.not_in_lobby", "You are not in this game"),
+    ("lobby.no_guest", "Nobody has joined yet"),
+    (
+        "lobby.failed_action",
+        "Something went wrong, please try again",
+    ),
+    ("game.not_found", "This game does not exist"),
+    ("game.turn", "Player {0}'s turn"),
+    ("game.won", "Player {0} won!"),
+    ("game.draw", "Draw, nobody can move"),
+    (
+        "game.takeback_request",
+        "Player {0} wants to take back their last move",
+    ),
+    ("game.accept", "Accept"),
+    ("game.decline", "Decline"),
+    (
+        "game.takeback_waiting",
+        "Waiting for your opponent to allow the takeback...",
+    ),
+    ("game.reset", "Reset"),
+    ("game.resign", "Resign"),
+    ("game.take_back", "Take back"),
+    ("game.undo", "Undo"),
+    ("game.redo", "Redo"),
+    ("game.name", "Game name"),
+    ("game.save_as", "Save as"),
+    ("move.drop", "Player {0} dropped a chip in column {1}"),
+    (
+        "move.push",
+        "Player {0} pushed a chip in from below in column {1}",
+    ),
+    ("move.pop", "Player {0} popped their chip out of column {1}"),
+    ("move.roll_up", "Player {0} rolled column {1} up"),
+    ("move.roll_down", "Player {0} rolled column {1} down"),
+    ("move.roll_right", "Player {0} rolled row {1} right"),
+    ("move.roll_left", "Player {0} rolled row {1} left"),
+    ("move.row", ", row {0}"),
+    ("cell.label", "Column {0}, row {1}: {2}"),
+    ("cell.player", "player {0}"),
+    ("cell.empty", "empty"),
+    ("cell.winning", ", winning"),
+    ("board.flat", "Flat board"),
+    ("board.tiled", "Tiled torus"),
+    ("board.torus", "3D torus"),
+    ("board.show_lines", "Show lines a drop would complete"),
+    ("board.reduce_motion", "Reduce motion"),
+    ("board.no_marks", "Don't mark moves"),
+    ("board.mark_moves.one", "Mark the last move"),
+    ("board.mark_moves.other", "Mark the last {0} moves"),
+    ("board.show_threats", "Show threats"),
+    ("board.pop", "Pop"),
+    ("board.push", "Push"),
+    ("board.popped", "A chip was popped out of this column"),
+    (
+        "board.label",
+        "Board, use the arrow keys to pick a column and Enter to drop, or type the column's number",
+    ),
+    ("board.recentre", "Recentre"),
+    ("home.profile", "Profile"),
+    ("game.seat", "Player {0}:"),
+    ("lobby.empty_seat", "empty seat"),
+    ("profile.anonymous", "Anonymous"),
+    ("profile.name", "Display name"),
+    ("profile.use_color", "Avatar colour"),
+    ("profile.save", "Save"),
+    ("profile.saved", "Saved"),
+    (
+        "profile.invalid",
+        "Names are 1 to 24 characters, without spaces at the start or end",
+    ),
+    ("profile.save_failed", "Failed to save the profile"),
+    (
+        "profile.failed",
+        "Failed to get the profile. Please refresh to try again",
+    ),
+    ("profile.rating", "Rating: {0}"),
+    ("profile.record", "{0} won, {1} lost, {2} drawn"),
+    ("profile.history", "Recent games"),
+    ("profile.no_games", "No finished games yet"),
+    ("profile.history_entry", "{0}: {1}, {2} against {3} ({4})"),
+    ("profile.won", "won"),
+    ("profile.lost", "lost"),
+    ("profile.draw", "draw"),
+    ("chat.players", "Players"),
+    ("chat.spectators", "Spectators"),
+    ("chat.empty", "No messages yet"),
+    ("chat.placeholder", "Write a message"),
+    ("chat.send", "Send"),
+    ("chat.mute", "Mute"),
+    ("chat.unmute", "Unmute"),
+    ("chat.hidden", "message hidden"),
+    ("chat.players_only", "Only the players can write here"),
+    (
+        "chat.players_write_elsewhere",
+        "Players write in their own channel",
+    ),
+    (
+        "chat.invalid",
+        "Messages can't be empty or longer than {0} characters",
+    ),
+    ("chat.wrong_channel", "You can't write in this channel"),
+    (
+        "chat.rate_limited",
+        "You can send {0} messages every {1} seconds, please wait a moment",
+    ),
+    (
+        "chat.failed",
+        "Failed to send the message, please try again",
+    ),
+    ("settings.title", "Settings"),
+    ("settings.colours", "Chip colours"),
+    ("settings.classic", "Classic"),
+    ("settings.okabe_ito", "Colour blind safe (Okabe-Ito)"),
+    ("settings.tol_bright", "Colour blind safe (Tol bright)"),
+    ("settings.dark_mode", "Dark mode"),
+    ("settings.chip_patterns", "Symbols on chips"),
+    ("settings.reduce_motion", "Reduce motion"),
+    ("settings.language", "Language"),
+];
+
+const DUTCH: Catalogue = &[
+    ("title", "Vier op een rij op een torus"),
+    ("not_found.title", "404: Pagina niet gevonden"),
+    (
+        "not_found.text",
+        "Het lijkt erop dat je op de verkeerde plek zoekt!",
+    ),
+    (
+        "offline.text",
+        "Je bent offline. Deze pagina wordt bijgewerkt zodra je weer verbinding hebt.",
+    ),
+    ("offline.local", "Lokale spellen werken zonder verbinding."),
+    ("home.find_game", "Spel zoeken"),
+    ("home.enter_name", "Voer de naam van het spel in"),
+    ("home.width", "Breedte"),
+    ("home.height", "Hoogte"),
+    ("home.connect", "Op een rij"),
+    ("home.create_game", "Spel aanmaken"),
+    ("home.local_game", "Lokaal spelen"),
+    ("home.resume", "Verdergaan"),
+    ("home.delete", "Verwijderen"),
+    ("home.settings", "Instellingen"),
+    (
+        "home.invalid_settings",
+        "Deze bordinstellingen zijn niet toegestaan",
+    ),
+    (
+        "home.offline",
+        "Je bent offline, online spellen hebben een verbinding nodig",
+    ),
+    ("home.create_failed", "Het spel kon niet worden aangemaakt"),
+    ("players.one", "{0} speler"),
+    ("players.other", "{0} spelers"),
+    ("players_joined.one", "{0} van de {1} spelers is binnen"),
+    ("players_joined.other", "{0} van de {1} spelers zijn binnen"),
+    ("first_player.host", "Host begint"),
+    ("first_player.guest", "Gast begint"),
+    ("first_player.random", "Willekeurige start"),
+    ("first_player.alternate", "Om de beurt beginnen"),
+    ("time_control.unlimited", "Geen tijdslimiet"),
+    ("time_control.blitz", "Blitz, minder dan een minuut per zet"),
+    ("time_control.rapid", "Rapid, een paar minuten per zet"),
+    (
+        "time_control.correspondence",
+        "Correspondentie, een dag per zet",
+    ),
+    ("variant.pop_out", "Pop Out"),
+    ("variant.roll_moves", "Rolzetten"),
+    ("variant.wrap_gravity", "Rondgaande zwaartekracht"),
+    ("list.patience", "Even geduld"),
+    ("list.fetching", "open spellen ophalen"),
+    ("list.continue", "Verder spelen"),
+    ("list.join_a_game", "Meedoen met een spel"),
+    (
+        "list.no_games",
+        "Er zijn geen open spellen. Ga naar de startpagina om er een te maken!",
+    ),
+    (
+        "list.failed",
+        "De gegevens konden niet worden opgehaald. Ververs de pagina om het opnieuw te proberen",
+    ),
+    ("list.join", "Meedoen!"),
+    ("list.open", "Openen"),
+    ("list.open_games.one", "{0} open spel"),
+    ("list.open_games.other", "{0} open spellen"),
+    ("list.search", "Zoeken"),
+    ("list.search_placeholder", "Naam van spel of gastheer"),
+    ("list.any", "Alle"),
+    ("list.min_rating", "Min. rating"),
+    ("list.max_rating", "Max. rating"),
+    ("list.time_control", "Bedenktijd"),
+    ("list.sort", "Sorteren op"),
+    ("list.sort.newest", "Nieuwste eerst"),
+    ("list.sort.oldest", "Oudste eerst"),
+    ("list.sort.highest_rating", "Hoogste rating gastheer"),
+    ("list.sort.lowest_rating", "Laagste rating gastheer"),
+    ("list.clear_filters", "Filters wissen"),
+    ("list.no_matches", "Geen open spellen voor deze filters"),
+    ("list.previous", "Vorige"),
+    ("list.next", "Volgende"),
+    ("list.page", "Pagina {0} van {1}"),
+    ("list.host_rating", "rating {0}"),
+    ("join.joined", "Je doet mee aan het spel"),
+    ("join.already_joined", "Je doet al mee aan dit spel"),
+    ("join.lobby_full", "Dit spel is al vol"),
+    ("join.failed", "Meedoen is mislukt, probeer het opnieuw"),
+    ("lobby.board", "{0}x{1} bord, {2} op een rij"),
+    ("lobby.closed", "Dit spel is gesloten"),
+    ("lobby.game_started", "Dit spel is al begonnen"),
+    ("lobby.waiting", "Wachten op tegenstanders..."),
+    ("lobby.starting", "Het spel begint..."),
+    ("lobby.kick", "Speler {0} verwijderen"),
+    ("lobby.cancel", "Spel annuleren"),
+    ("lobby.leave", "Spel verlaten"),
+    (
+        "lobby.failed",
+        "De lobby kon niet worden opgehaald. Ververs de pagina om het opnieuw te proberen",
+    ),
+    ("lobby.wait", "Even wachten..."),
+    ("lobby.done", "Klaar"),
+    ("lobby.not_host", "Alleen de host kan dit doen"),
+    ("lobby.not_in_lobby", "Je doet niet mee aan dit spel"),
+    ("lobby.no_guest", "Er doet nog niemand mee"),
+    (
+        "lobby.failed_action",
+        "Er ging iets mis, probeer het opnieuw",
+    ),
+    ("game.not_found", "Dit spel bestaat niet"),
+    ("game.turn", "Speler {0} is aan de beurt"),
+    ("game.won", "Speler {0} heeft gewonnen!"),
+    ("game.draw", "Gelijkspel, niemand kan nog zetten"),
+    (
+        "game.takeback_request",
+        "Speler {0} wil de laatste zet terugnemen",
+    ),
+    ("game.accept", "Accepteren"),
+    ("game.decline", "Weigeren"),
+    (
+        "game.takeback_waiting",
+        "Wachten tot je tegenstander het terugnemen toestaat...",
+    ),
+    ("game.reset", "Opnieuw beginnen"),
+    ("game.resign", "Opgeven"),
+    ("game.take_back", "Terugnemen"),
+    ("game.undo", "Ongedaan maken"),
+    ("game.redo", "Opnieuw doen"),
+    ("game.name", "Naam van het spel"),
+    ("game.save_as", "Opslaan als"),
+    ("move.drop", "Speler {0} liet een fiche vallen in kolom {1}"),
+    (
+        "move.push",
+        "Speler {0} duwde een fiche van onderen in kolom {1}",
+    ),
+    (
+        "move.pop",
+        "Speler {0} haalde een eigen fiche uit kolom {1}",
+    ),
+    ("move.roll_up", "Speler {0} rolde kolom {1} omhoog"),
+    ("move.roll_down", "Speler {0} rolde kolom {1} omlaag"),
+    ("move.roll_right", "Speler {0} rolde rij {1} naar rechts"),
+    ("move.roll_left", "Speler {0} rolde rij {1} naar links"),
+    ("move.row", ", rij {0}"),
+    ("cell.label", "Kolom {0}, rij {1}: {2}"),
+    ("cell.player", "speler {0}"),
+    ("cell.empty", "leeg"),
+    ("cell.winning", ", winnend"),
+    ("board.flat", "Plat bord"),
+    ("board.tiled", "Betegelde torus"),
+    ("board.torus", "3D-torus"),
+    ("board.show_lines", "Toon rijen die een zet zou voltooien"),
+    ("board.reduce_motion", "Minder beweging"),
+    ("board.no_marks", "Zetten niet markeren"),
+    ("board.mark_moves.one", "Markeer de laatste zet"),
+    ("board.mark_moves.other", "Markeer de laatste {0} zetten"),
+    ("board.show_threats", "Toon dreigingen"),
+    ("board.pop", "Eruit"),
+    ("board.push", "Erin"),
+    ("board.popped", "Uit deze kolom is een fiche gehaald"),
+    (
+        "board.label",
+        "Bord, kies een kolom met de pijltjestoetsen en Enter, of typ het nummer van de kolom",
+    ),
+    ("board.recentre", "Centreren"),
+    ("home.profile", "Profiel"),
+    ("game.seat", "Speler {0}:"),
+    ("lobby.empty_seat", "lege plaats"),
+    ("profile.anonymous", "Anoniem"),
+    ("profile.name", "Weergavenaam"),
+    ("profile.use_color", "Avatarkleur"),
+    ("profile.save", "Opslaan"),
+    ("profile.saved", "Opgeslagen"),
+    (
+        "profile.invalid",
+        "Namen zijn 1 tot 24 tekens, zonder spaties aan het begin of eind",
+    ),
+    (
+        "profile.save_failed",
+        "Het profiel kon niet worden opgeslagen",
+    ),
+    (
+        "profile.failed",
+        "Het profiel kon niet worden opgehaald. Ververs de pagina om het opnieuw te proberen",
+    ),
+    ("profile.rating", "Rating: {0}"),
+    ("profile.record", "{0} gewonnen, {1} verloren, {2} gelijk"),
+    ("profile.history", "Recente spellen"),
+    ("profile.no_games", "Nog geen afgelopen spellen"),
+    ("profile.history_entry", "{0}: {1}, {2} tegen {3} ({4})"),
+    ("profile.won", "gewonnen"),
+    ("profile.lost", "verloren"),
+    ("profile.draw", "gelijk"),
+    ("chat.players", "Spelers"),
+    ("chat.spectators", "Toeschouwers"),
+    ("chat.empty", "Nog geen berichten"),
+    ("chat.placeholder", "Schrijf een bericht"),
+    ("chat.send", "Versturen"),
+    ("chat.mute", "Dempen"),
+    ("chat.unmute", "Niet meer dempen"),
+    ("chat.hidden", "bericht verborgen"),
+    (
+        "chat.players_only",
+        "Alleen de spelers kunnen hier schrijven",
+    ),
+    (
+        "chat.players_write_elsewhere",
+        "Spelers schrijven in hun eigen kanaal",
+    ),
+    (
+        "chat.invalid",
+        "Berichten mogen niet leeg zijn of langer dan {0} tekens",
+    ),
+    ("chat.wrong_channel", "Je kunt niet in dit kanaal schrijven"),
+    (
+        "chat.rate_limited",
+        "Je kunt {0} berichten per {1} seconden sturen, wacht even",
+    ),
+    (
+        "chat.failed",
+        "Het bericht kon niet worden verstuurd, probeer het opnieuw",
+    ),
+    ("settings.title", "Instellingen"),
+    ("settings.colours", "Kleuren van de fiches"),
+    ("settings.classic", "Klassiek"),
+    (
+        "settings.okabe_ito",
+        "Veilig bij kleurenblindheid (Okabe-Ito)",
+    ),
+    (
+        "settings.tol_bright",
+        "Veilig bij kleurenblindheid (Tol bright)",
+    ),
+    ("settings.dark_mode", "Donkere modus"),
+    ("settings.chip_patterns", "Symbolen op fiches"),
+    ("settings.reduce_motion", "Minder beweging"),
+    ("settings.language", "Taal"),
+];
+
+const GERMAN: Catalogue = &[
+    ("title", "Vier gewinnt auf einem Torus"),
+    ("not_found.title", "404: Seite nicht gefunden"),
+    (
+        "not_found.text",
+        "Du suchst anscheinend an der falschen Stelle!",
+    ),
+    (
+        "offline.text",
+        "Du bist offline. Diese Seite wird aktualisiert, sobald du wieder verbunden bist.",
+    ),
+    (
+        "offline.local",
+        "Lokale Spiele funktionieren ohne Verbindung.",
+    ),
+    ("home.find_game", "Spiel finden"),
+    ("home.enter_name", "Spielnamen eingeben"),
+    ("home.width", "Breite"),
+    ("home.height", "Höhe"),
+    ("home.connect", "In einer Reihe"),
+    ("home.create_game", "Spiel erstellen"),
+    ("home.local_game", "Lokal spielen"),
+    ("home.resume", "Fortsetzen"),
+    ("home.delete", "Löschen"),
+    ("home.settings", "Einstellungen"),
+    (
+        "home.invalid_settings",
+        "Diese Brett-Einstellungen sind nicht erlaubt",
+    ),
+    (
+        "home.offline",
+        "Du bist offline, Online-Spiele brauchen eine Verbindung",
+    ),
+    (
+        "home.create_failed",
+        "Das Spiel konnte nicht erstellt werden",
+    ),
+    ("players.one", "{0} Spieler"),
+    ("players.other", "{0} Spieler"),
+    ("players_joined.one", "{0} von {1} Spielern ist beigetreten"),
+    (
+        "players_joined.other",
+        "{0} von {1} Spielern sind beigetreten",
+    ),
+    ("first_player.host", "Gastgeber beginnt"),
+    ("first_player.guest", "Gast beginnt"),
+    ("first_player.random", "Zufälliger Start"),
+    ("first_player.alternate", "Abwechselnder Start"),
+    ("time_control.unlimited", "Ohne Zeitlimit"),
+    ("time_control.blitz", "Blitz, unter einer Minute pro Zug"),
+    (
+        "time_control.rapid",
+        "Schnellpartie, einige Minuten pro Zug",
+    ),
+    ("time_control.correspondence", "Fernpartie, ein Tag pro Zug"),
+    ("variant.pop_out", "Pop Out"),
+    ("variant.roll_moves", "Rollzüge"),
+    ("variant.wrap_gravity", "Umlaufende Schwerkraft"),
+    ("list.patience", "Bitte etwas Geduld"),
+    ("list.fetching", "offene Spiele werden geladen"),
+    ("list.continue", "Weiterspielen"),
+    ("list.join_a_game", "Einem Spiel beitreten"),
+    (
+        "list.no_games",
+        "Es gibt keine offenen Spiele. Erstelle auf der Startseite ein neues!",
+    ),
+    (
+        "list.failed",
+        "Die Daten konnten nicht geladen werden. Lade die Seite neu, um es erneut zu versuchen",
+    ),
+    ("list.join", "Beitreten!"),
+    ("list.open", "Öffnen"),
+    ("list.open_games.one", "{0} offenes Spiel"),
+    ("list.open_games.other", "{0} offene Spiele"),
+    ("list.search", "Suchen"),
+    ("list.search_placeholder", "Spiel- oder Gastgebername"),
+    ("list.any", "Alle"),
+    ("list.min_rating", "Min. Wertung"),
+    ("list.max_rating", "Max. Wertung"),
+    ("list.time_control", "Bedenkzeit"),
+    ("list.sort", "Sortieren nach"),
+    ("list.sort.newest", "Neueste zuerst"),
+    ("list.sort.oldest", "Älteste zuerst"),
+    ("list.sort.highest_rating", "Höchste Wertung des Gastgebers"),
+    (
+        "list.sort.lowest_rating",
+        "Niedrigste Wertung des Gastgebers",
+    ),
+    ("list.clear_filters", "Filter zurücksetzen"),
+    ("list.no_matches", "Keine offenen Spiele für diese Filter"),
+    ("list.previous", "Zurück"),
+    ("list.next", "Weiter"),
+    ("list.page", "Seite {0} von {1}"),
+    ("list.host_rating", "Wertung {0}"),
+    ("join.joined", "Du bist dem Spiel beigetreten"),
+    ("join.already_joined", "Du bist bereits in diesem Spiel"),
+    ("join.lobby_full", "Dieses Spiel ist bereits voll"),
+    (
+        "join.failed",
+        "Beitreten fehlgeschlagen, bitte versuche es erneut",
+    ),
+    ("lobby.board", "{0}x{1} Brett, {2} in einer Reihe"),
+    ("lobby.closed", "Dieses Spiel wurde geschlossen"),
+    ("lobby.game_started", "Dieses Spiel hat bereits begonnen"),
+    ("lobby.waiting", "Warten auf Gegner..."),
+    ("lobby.starting", "Das Spiel startet..."),
+    ("lobby.kick", "Spieler {0} entfernen"),
+    ("lobby.cancel", "Spiel abbrechen"),
+    ("lobby.leave", "Spiel verlassen"),
+    (
+        "lobby.failed",
+        "Die Lobby konnte nicht geladen werden. Lade die Seite neu, um es erneut zu versuchen",
+    ),
+    ("lobby.wait", "Bitte warten..."),
+    ("lobby.done", "Erledigt"),
+    ("lobby.not_host", "Nur der Gastgeber kann das tun"),
+    ("lobby.not_in_lobby", "Du bist nicht in diesem Spiel"),
+    ("lobby.no_guest", "Es ist noch niemand beigetreten"),
+    (
+        "lobby.failed_action",
+        "Etwas ist schiefgelaufen, bitte versuche es erneut",
+    ),
+    ("game.not_found", "Dieses Spiel existiert nicht"),
+    ("game.turn", "Spieler {0} ist am Zug"),
+    ("game.won", "Spieler {0} hat gewonnen!"),
+    ("game.draw", "Unentschieden, niemand kann mehr ziehen"),
+    (
+        "game.takeback_request",
+        "Spieler {0} möchte den letzten Zug zurücknehmen",
+    ),
+    ("game.accept", "Annehmen"),
+    ("game.decline", "Ablehnen"),
+    (
+        "game.takeback_waiting",
+        "Warten, bis dein Gegner die Rücknahme erlaubt...",
+    ),
+    ("game.reset", "Neu starten"),
+    ("game.resign", "Aufgeben"),
+    ("game.take_back", "Zurücknehmen"),
+    ("game.undo", "Rückgängig"),
+    ("game.redo", "Wiederholen"),
+    ("game.name", "Spielname"),
+    ("game.save_as", "Speichern als"),
+    (
+        "move.drop",
+        "Spieler {0} hat einen Stein in Spalte {1} geworfen",
+    ),
+    (
+        "move.push",
+        "Spieler {0} hat einen Stein von unten in Spalte {1} geschoben",
+    ),
+    (
+        "move.pop",
+        "Spieler {0} hat einen eigenen Stein aus Spalte {1} entfernt",
+    ),
+    (
+        "move.roll_up",
+        "Spieler {0} hat Spalte {1} nach oben gerollt",
+    ),
+    (
+        "move.roll_down",
+        "Spieler {0} hat Spalte {1} nach unten gerollt",
+    ),
+    (
+        "move.roll_right",
+        "Spieler {0} hat Reihe {1} nach rechts gerollt",
+    ),
+    (
+        "move.roll_left",
+        "Spieler {0} hat Reihe {1} nach links gerollt",
+    ),
+    ("move.row", ", Reihe {0}"),
+    ("cell.label", "Spalte {0}, Reihe {1}: {2}"),
+    ("cell.player", "Spieler {0}"),
+    ("cell.empty", "leer"),
+    ("cell.winning", ", gewinnend"),
+    ("board.flat", "Flaches Brett"),
+    ("board.tiled", "Gekachelter Torus"),
+    ("board.torus", "3D-Torus"),
+    (
+        "board.show_lines",
+        "Reihen zeigen, die ein Zug vervollständigen würde",
+    ),
+    ("board.reduce_motion", "Weniger Bewegung"),
+    ("board.no_marks", "Züge nicht markieren"),
+    ("board.mark_moves.one", "Den letzten Zug markieren"),
+    ("board.mark_moves.other", "Die letzten {0} Züge markieren"),
+    ("board.show_threats", "Drohungen zeigen"),
+    ("board.pop", "Raus"),
+    ("board.push", "Rein"),
+    (
+        "board.popped",
+        "Aus dieser Spalte wurde ein Stein herausgenommen",
+    ),
+    (
+        "board.label",
+        "Brett, wähle mit den Pfeiltasten eine Spalte und drücke Enter, oder tippe ihre Nummer",
+    ),
+    ("board.recentre", "Zentrieren"),
+    ("home.profile", "Profil"),
+    ("game.seat", "Spieler {0}:"),
+    ("lobby.empty_seat", "freier Platz"),
+    ("profile.anonymous", "Anonym"),
+    ("profile.name", "Anzeigename"),
+    ("profile.use_color", "Avatarfarbe"),
+    ("profile.save", "Speichern"),
+    ("profile.saved", "Gespeichert"),
+    (
+        "profile.invalid",
+        "Namen haben 1 bis 24 Zeichen, ohne Leerzeichen am Anfang oder Ende",
+    ),
+    (
+        "profile.save_failed",
+        "Das Profil konnte nicht gespeichert werden",
+    ),
+    (
+        "profile.failed",
+        "Das Profil konnte nicht geladen werden. Lade die Seite neu, um es erneut zu versuchen",
+    ),
+    ("profile.rating", "Wertung: {0}"),
+    (
+        "profile.record",
+        "{0} gewonnen, {1} verloren, {2} unentschieden",
+    ),
+    ("profile.history", "Letzte Spiele"),
+    ("profile.no_games", "Noch keine beendeten Spiele"),
+    ("profile.history_entry", "{0}: {1}, {2} gegen {3} ({4})"),
+    ("profile.won", "gewonnen"),
+    ("profile.lost", "verloren"),
+    ("profile.draw", "unentschieden"),
+    ("chat.players", "Spieler"),
+    ("chat.spectators", "Zuschauer"),
+    ("chat.empty", "Noch keine Nachrichten"),
+    ("chat.placeholder", "Schreibe eine Nachricht"),
+    ("chat.send", "Senden"),
+    ("chat.mute", "Stummschalten"),
+    ("chat.unmute", "Stummschaltung aufheben"),
+    ("chat.hidden", "Nachricht ausgeblendet"),
+    ("chat.players_only", "Nur die Spieler können hier schreiben"),
+    (
+        "chat.players_write_elsewhere",
+        "Spieler schreiben in ihrem eigenen Kanal",
+    ),
+    (
+        "chat.invalid",
+        "Nachrichten dürfen nicht leer oder länger als {0} Zeichen sein",
+    ),
+    (
+        "chat.wrong_channel",
+        "Du kannst in diesem Kanal nicht schreiben",
+    ),
+    (
+        "chat.rate_limited",
+        "Du kannst {0} Nachrichten alle {1} Sekunden senden, bitte warte kurz",
+    ),
+    (
+        "chat.failed",
+        "Die Nachricht konnte nicht gesendet werden, bitte versuche es erneut",
+    ),
+    ("settings.title", "Einstellungen"),
+    ("settings.colours", "Steinfarben"),
+    ("settings.classic", "Klassisch"),
+    (
+        "settings.okabe_ito",
+        "Sicher bei Farbenblindheit (Okabe-Ito)",
+    ),
+    (
+        "settings.tol_bright",
+        "Sicher bei Farbenblindheit (Tol bright)",
+    ),
+    ("settings.dark_mode", "Dunkelmodus"),
+    ("settings.chip_patterns", "Symbole auf Steinen"),
+    ("settings.reduce_motion", "Weniger Bewegung"),
+    ("settings.language", "Sprache"),
+];
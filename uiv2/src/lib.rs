@@ -1,18 +1,21 @@
-// TODO: remove common, replace ui stuff. It's too much hassle to have to create a wrapper class to impl
-// yew stuff
-use core::fmt;
-use serde::{Deserialize, Serialize};
+use common::IdType;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
 pub mod board;
 mod cell;
+mod chat;
+mod drag;
 pub mod gamelist;
-use board::{Board, BoardView};
+mod tiledboard;
+mod torusview;
+use board::BoardView;
 use gamelist::GameListView;
 mod database;
 mod homepage;
+mod i18n;
 use homepage::HomePage;
+use i18n::{t, Language};
 pub mod connectgame;
 mod lobby;
 use lobby::GameLobbyView;
@@ -22,48 +25,12 @@ use localconnectgame::LocalGame;
 mod notfound;
 use notfound::NotFoundPage;
 pub mod cookies;
-
-pub type IdType = u32;
-
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
-
-pub enum Player {
-    One,
-    Two,
-}
-
-impl Into<u8> for Player {
-    fn into(self) -> u8 {
-        match self {
-            Player::One => 1,
-            Player::Two => 2,
-        }
-    }
-}
-
-impl TryFrom<u8> for Player {
-    type Error = String;
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value {
-            1 => Ok(Player::One),
-            2 => Ok(Player::Two),
-            _ => Err("Invalid player identifier".to_owned()),
-        }
-    }
-}
-
-impl fmt::Display for Player {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "p{}",
-            match self {
-                Player::One => "1",
-                Player::Two => "2",
-            }
-        )
-    }
-}
+mod offline;
+mod profile;
+use profile::ProfilePage;
+mod settings;
+use settings::{Settings, SettingsPage};
+mod storage;
 
 #[derive(PartialEq, Clone, Routable)]
 pub enum Pages {
@@ -77,6 +44,14 @@ pub enum Pages {
     Lobby { game_id: String },
     #[at("/localgame")]
     Local,
+    #[at("/localgame/:name")]
+    SavedLocal { name: String },
+    #[at("/settings")]
+    Settings,
+    #[at("/profile")]
+    OwnProfile,
+    #[at("/profile/:player_id")]
+    Profile { player_id: IdType },
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -105,6 +80,10 @@ fn switch(routes: Pages) -> Html {
             html! {<GameLobbyView game_id = {game_id.parse::<IdType>().unwrap()}/>}
         }
         Pages::Local => html! {<LocalGame/>},
+        Pages::SavedLocal { name } => html! {<LocalGame {name}/>},
+        Pages::Settings => html! {<SettingsPage/>},
+        Pages::OwnProfile => html! {<ProfilePage/>},
+        Pages::Profile { player_id } => html! {<ProfilePage player_id={player_id}/>},
         Pages::NotFound => html! {<NotFoundPage/>},
     }
 }
@@ -114,7 +93,7 @@ fn Title() -> Html {
     let navigator = use_navigator().unwrap();
     let return_homepage = Callback::from(move |_| navigator.push(&Pages::HomePage));
     html! {<button onclick={return_homepage} style="all:unset;cursor:pointer;">
-        <h1>{"Connect 4 on a Torus"}</h1>
+        <h1>{t("title")}</h1>
     </button>}
 }
 
@@ -134,6 +113,14 @@ fn Title() -> Html {
 #[function_component]
 pub fn App() -> Html {
     wasm_logger::init(wasm_logger::Config::default());
+    use_effect_with_deps(
+        |_| {
+            Settings::load().apply();
+            Language::current().apply();
+            || ()
+        },
+        (),
+    );
 
     html! {
         <div class="mainpage">
@@ -1,6 +1,14 @@
-use crate::IdType;
-use crate::{connectgame::GameData, gamelist::GameLobby, Pages};
-use reqwasm::http::Request;
+use crate::chat::ChatPanel;
+use crate::cookies::get_player_id;
+use crate::database::{get_object, lobby_action};
+use crate::i18n::{first_player_text, lobby_action_text, t, tf, time_control_text, tn};
+use crate::offline::{is_offline, OfflineNotice};
+use crate::profile::use_player_infos;
+use crate::Pages;
+use common::lobby::{GameLobby, LobbyAction, LobbyActionResult, LobbyState};
+use common::rules::Variants;
+use common::IdType;
+use gloo_timers::callback::Interval;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use yew_router::prelude::*;
@@ -85,12 +93,27 @@ pub struct GameLobbyProps {
 //     // }
 // }
 
+/// The enabled rule variants as a list continuing the settings line, e.g. ", Pop Out"
+fn variants_text(variants: &Variants) -> String {
+    [
+        (variants.pop_out, "variant.pop_out"),
+        (variants.roll_moves, "variant.roll_moves"),
+        (variants.wrap_gravity, "variant.wrap_gravity"),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, key)| format!(", {}", t(key)))
+    .collect()
+}
+
 #[derive(PartialEq)]
 enum FetchState {
     NotFetching,
     Fetching,
     Success,
     Failure,
+    /// The browser has no connection, the lobby keeps being fetched until it is back
+    Offline,
 }
 
 #[function_component]
@@ -98,88 +121,150 @@ pub fn GameLobbyView(game_lobby_props: &GameLobbyProps) -> Html {
     let game_id = game_lobby_props.game_id;
     let gamelobby_state = use_state(|| None);
     let fetch_state = use_state(|| FetchState::NotFetching);
+    let navigator = use_navigator().unwrap();
+
+    {
+        // refetch the lobby every second, so joins/leaves show up and both players get sent to the game
+        let fetch_state = fetch_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                let interval =
+                    Interval::new(1000, move || fetch_state.set(FetchState::NotFetching));
+                move || drop(interval)
+            },
+            (),
+        );
+    }
+
     let gamelobby_state_clone = gamelobby_state.clone();
     if *fetch_state == FetchState::NotFetching {
         fetch_state.set(FetchState::Fetching);
+        let fetch_state = fetch_state.clone();
+        let navigator = navigator.clone();
         spawn_local(async move {
-            // let gamelobby: GameLobby = Request::get(&format!("/api/gamelobby/{}", game_id))
-            //     .send()
-            //     .await
-            //     .unwrap()
-            //     .json()
-            //     .await
-            //     .unwrap();
-            // gamelobby_state.set(Some(gamelobby));
-
-            let gamelobby_result: Result<GameLobby, _> =
-                Request::get(&format!("/api/gamelobby/{}", game_id))
-                    .send()
-                    .await
-                    .unwrap()
-                    .json()
-                    .await;
-
-            match gamelobby_result {
-                Ok(gamelobby) => gamelobby_state.set(Some(gamelobby)),
-                Err(err) => log::info!("Got error: {}", err),
+            match get_object::<GameLobby>(&format!("/api/gamelobby/{}", game_id)).await {
+                Ok(gamelobby) => {
+                    // the backend creates the game and starts the lobby as soon as it is full
+                    let seated = gamelobby.seat_of(get_player_id()).is_some();
+                    if gamelobby.state == LobbyState::Started && seated {
+                        navigator.push(&Pages::Game { game_id });
+                    }
+                    gamelobby_state.set(Some(gamelobby));
+                    fetch_state.set(FetchState::Success);
+                }
+                Err(err) => {
+                    log::info!("Got error: {}", err);
+                    fetch_state.set(match is_offline() {
+                        true => FetchState::Offline,
+                        false => FetchState::Failure,
+                    });
+                }
             }
-            // let log::info!("Yeet!");
         });
-        fetch_state.set(FetchState::Success);
     }
-    let navigator = use_navigator().unwrap();
-    let body_html = match gamelobby_state_clone.as_ref() {
-        Some(gamelobby) => {
-            let gamelobby = gamelobby_state_clone.as_ref().unwrap().clone();
-
-            let startable = gamelobby.number_players_joined() == 2;
-
-            let start_game = Callback::from(move |_| {
-                if !startable {
-                    return;
-                }
-
-                if gamelobby.game_started {
-                    navigator.push(&Pages::Game { game_id });
-                    return;
-                }
-
-                let gamedata = GameData::new(
-                    7,
-                    6,
-                    4,
-                    gamelobby.game_id,
-                    gamelobby.player1_id.unwrap(), // is safe due to startable check
-                    gamelobby.player2_id.unwrap(),
-                );
-
-                log::info!(
-                    "JSON analysis: {}",
-                    serde_json::to_string(&gamedata).unwrap()
-                );
 
+    let action_error = use_state(|| None::<LobbyActionResult>);
+    let player_infos = use_player_infos(
+        gamelobby_state_clone
+            .as_ref()
+            .map(|gamelobby: &GameLobby| gamelobby.seats.iter().flatten().copied().collect())
+            .unwrap_or_default(),
+    );
+    let action_callback = {
+        let navigator = navigator.clone();
+        let fetch_state = fetch_state.clone();
+        let action_error = action_error.clone();
+        move |action: LobbyAction| {
+            let navigator = navigator.clone();
+            let fetch_state = fetch_state.clone();
+            let action_error = action_error.clone();
+            Callback::from(move |_| {
+                let navigator = navigator.clone();
+                let fetch_state = fetch_state.clone();
+                let action_error = action_error.clone();
                 spawn_local(async move {
-                    Request::post("/api/create_game")
-                        .body(serde_json::to_string(&gamedata).unwrap())
-                        .send()
+                    let result = lobby_action(action, game_id)
                         .await
-                        .unwrap();
+                        .unwrap_or(LobbyActionResult::Failed);
+                    match (result, action) {
+                        (LobbyActionResult::Done, LobbyAction::Kick(_)) => {
+                            action_error.set(None);
+                            fetch_state.set(FetchState::NotFetching);
+                        }
+                        (LobbyActionResult::Done, _) => navigator.push(&Pages::GameList),
+                        (result, _) => action_error.set(Some(result)),
+                    }
                 });
-                navigator.push(&Pages::Game {
-                    game_id: gamelobby.game_id,
-                });
-            });
+            })
+        }
+    };
+    let body_html = match gamelobby_state_clone.as_ref() {
+        Some(gamelobby) if gamelobby.state.is_closed() => {
+            html! {<p>{t("lobby.closed")}</p>}
+        }
+        Some(gamelobby) => {
+            let player_id = get_player_id();
+            let is_host = gamelobby.host_id() == Some(player_id);
+            let is_guest = !is_host && gamelobby.seat_of(player_id).is_some();
+            let waiting = gamelobby.state.is_waiting();
+            let players_joined = gamelobby.number_players_joined();
+
             html! {
                 <>
-                <p>{format!("{} players have joined", gamelobby.number_players_joined())}</p>
-                <button class={match startable {
-                    true => "greenbutton",
-                    false => "graybutton"
-                }} onclick = {start_game}> {"Start game"} </button>
+                <p>{tn("players_joined", players_joined, &[&gamelobby.seats.len()])}</p>
+                <p>{format!(
+                    "{}, {}, {}{}",
+                    tf(
+                        "lobby.board",
+                        &[
+                            &gamelobby.settings.width,
+                            &gamelobby.settings.height,
+                            &gamelobby.settings.win_length,
+                        ],
+                    ),
+                    first_player_text(gamelobby.settings.first_player),
+                    time_control_text(gamelobby.settings.time_control),
+                    variants_text(&gamelobby.settings.variants)
+                )}</p>
+                <p>{match gamelobby.state {
+                    LobbyState::Open => t("lobby.waiting"),
+                    _ => t("lobby.starting"),
+                }}</p>
+                <div class="smallblock">
+                    {gamelobby.seats.iter().enumerate().map(|(seat, occupant)| html! {
+                        <div>
+                            {tf("game.seat", &[&(seat + 1)])}
+                            if let Some(player_id) = occupant {
+                                {player_infos.link(*player_id)}
+                            } else {
+                                {" "}{t("lobby.empty_seat")}
+                            }
+                            if waiting && is_host && seat != 0 && occupant.is_some() {
+                                <button class="greenbutton" onclick={action_callback(LobbyAction::Kick(seat))}>
+                                    {tf("lobby.kick", &[&(seat + 1)])}
+                                </button>
+                            }
+                        </div>
+                    }).collect::<Html>()}
+                </div>
+                if waiting && is_host {
+                    <button class="greenbutton" onclick={action_callback(LobbyAction::Cancel)}>{t("lobby.cancel")}</button>
+                }
+                if waiting && is_guest {
+                    <button class="greenbutton" onclick={action_callback(LobbyAction::Leave)}>{t("lobby.leave")}</button>
+                }
+                if let Some(result) = *action_error {
+                    <p>{lobby_action_text(&result)}</p>
+                }
+                <ChatPanel {game_id} seated={is_host || is_guest}/>
                 </>
             }
         }
-        None => html! {"Please wait..."},
+        None if *fetch_state == FetchState::Offline => html! {<OfflineNotice/>},
+        None if *fetch_state == FetchState::Failure => {
+            html! {t("lobby.failed")}
+        }
+        None => html! {t("lobby.wait")},
     };
 
     html! {<div class="smallblock">
@@ -1,136 +1,329 @@
-use crate::{Board, BoardView, Player};
+use crate::cell::status_html;
+use crate::i18n::{t, tn};
+use crate::{storage, BoardView, Pages};
+use common::game::GameData;
+use common::lobby::FirstPlayer;
+use common::rules::{Move, Variants};
+use common::Player;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::mem;
+use std::collections::BTreeMap;
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
+use yew_router::scope_ext::RouterScopeExt;
+
+const STORAGE_KEY: &str = "local_games";
+
+/// The saved games as stored, each one is decoded on its own so a game that can't be read is
+/// skipped instead of taking the others with it, and kept when another game is saved
+fn stored_games() -> BTreeMap<String, serde_json::Value> {
+    storage::load(STORAGE_KEY).unwrap_or_default()
+}
+
+/// Local games saved in this browser by name. The game at /localgame is saved under the empty name.
+pub fn saved_games() -> BTreeMap<String, LocalGame> {
+    stored_games()
+        .into_iter()
+        .filter_map(|(name, json)| Some((name, serde_json::from_value(json).ok()?)))
+        .collect()
+}
+
+fn save_game(name: &str, game: &LocalGame) {
+    let json = match serde_json::to_value(game) {
+        Ok(json) => json,
+        Err(err) => {
+            log::info!("Failed to serialize game {}: {}", name, err);
+            return;
+        }
+    };
+    let mut games = stored_games();
+    games.insert(name.to_owned(), json);
+    storage::save(STORAGE_KEY, &games);
+}
+
+pub fn delete_saved_game(name: &str) {
+    let mut games = stored_games();
+    games.remove(name);
+    storage::save(STORAGE_KEY, &games);
+}
+
+/// Names end up in the url, so only letters, digits, '-' and '_' are kept and spaces become '-'
+fn clean_game_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c == ' ' { '-' } else { c })
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
 
 pub enum Msg {
-    ColumnClick(usize),
+    Play(Move),
     Reset,
+    /// Resigns the turn player
+    Resign,
+    SetNumPlayers(u8),
+    SetVariants(Variants),
+    Undo,
+    Redo,
+    /// Saves a copy of the game under a new name and continues with that copy
+    SaveAs(String),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct LocalGameProps {
+    /// Name the game is saved under, empty for the game at /localgame
+    #[prop_or_default]
+    pub name: String,
 }
 
+#[derive(PartialEq, Properties)]
+struct SaveAsProps {
+    on_save: Callback<String>,
+}
+
+/// Text field and button to save the game under a name
+#[function_component(SaveAs)]
+fn save_as(props: &SaveAsProps) -> Html {
+    let name = use_state(String::new);
+    let oninput = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let target: Option<EventTarget> = e.target();
+            if let Some(input) = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok()) {
+                name.set(clean_game_name(&input.value()));
+            }
+        })
+    };
+    let onclick = {
+        let name = name.clone();
+        let on_save = props.on_save.clone();
+        Callback::from(move |_| on_save.emit((*name).clone()))
+    };
+    html! {
+        <div class="smallblock">
+            <input type="text" placeholder={t("game.name")} value={(*name).clone()} {oninput}/>
+            <button {onclick} disabled={name.is_empty()}>{t("game.save_as")}</button>
+        </div>
+    }
+}
+
+/// A game played by several players on one device. The rules are those of online games, with
+/// undone moves kept so they can be redone.
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct LocalGame {
-    pub board: Board,
-    pub win_length: usize,
-    pub turn_player: Player,
-    pub win_status: Option<Player>,
-    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    /// Local games have no id and their players no session ids, those are all 0
+    pub game: GameData,
+    /// Undone moves that can be redone, the last one undone is at the end
+    pub undone_moves: Vec<Move>,
 }
 
 impl LocalGame {
-    pub fn new(width: u8, height: u8, win_length: usize) -> Self {
+    pub fn new(width: u8, height: u8, win_length: u8, num_players: u8, variants: Variants) -> Self {
         Self {
-            board: Board::new(width, height),
-            win_length,
-            turn_player: Player::One,
-            win_status: None,
-            winning_chips: None,
+            game: GameData::new(
+                width,
+                height,
+                win_length,
+                0,
+                vec![0; num_players as usize],
+                FirstPlayer::Host,
+                variants,
+            ),
+            undone_moves: Vec::new(),
         }
     }
 
-    pub fn reset(&mut self) {
-        let _ = mem::replace(
-            self,
-            Self::new(self.board.width, self.board.height, self.win_length),
+    /// The game saved under `name`, or a new game if there is none
+    pub fn load(name: &str) -> Self {
+        saved_games()
+            .remove(name)
+            .unwrap_or_else(|| Self::new(7, 6, 4, Player::MIN_PLAYERS, Variants::default()))
+    }
+
+    /// Starts over with the same board size and rules, `num_players` players and `variants`
+    fn restart(&mut self, num_players: u8, variants: Variants) {
+        let board = &self.game.board;
+        *self = Self::new(
+            board.width,
+            board.height,
+            self.game.win_length,
+            num_players,
+            variants,
         );
     }
-    pub fn replace(&mut self, replacement: Self) {
-        let _ = mem::replace(self, replacement);
+
+    pub fn reset(&mut self) {
+        self.restart(self.game.num_players(), self.game.variants);
     }
 
-    // pub fn check_win(&self, col: usize, row: usize, player: &Player) -> Option<(i32, i32)> {
-    //     self.board.check_win(col, row, player, self.win_length)
-    // }
+    /// Plays `mv` for the turn player, returns false if the move is not allowed
+    pub fn play_move(&mut self, mv: Move) -> bool {
+        self.game.play_move(mv).is_ok()
+    }
+
+    pub fn undo(&mut self) {
+        let Some(mv) = self.game.moves.last().map(|played| played.mv) else {
+            return;
+        };
+        if self.game.undo().is_some() {
+            self.undone_moves.push(mv);
+        }
+    }
 
-    pub fn next_turn(&mut self) {
-        self.turn_player = match self.turn_player {
-            Player::One => Player::Two,
-            Player::Two => Player::One,
+    pub fn redo(&mut self) {
+        if let Some(mv) = self.undone_moves.pop() {
+            self.play_move(mv);
         }
     }
+
+    /// Takes the turn player out of the game, the last player left wins
+    pub fn resign(&mut self) {
+        let player = self.game.turn_player.clone();
+        self.game.resign(player);
+    }
 }
 
 impl Component for LocalGame {
     type Message = Msg;
-    type Properties = (); // maybe win_length should be in here to properly pass to board?
+    type Properties = LocalGameProps; // maybe win_length should be in here to properly pass to board?
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        Self::new(7, 6, 4)
+    fn create(ctx: &Context<Self>) -> Self {
+        Self::load(&ctx.props().name)
     }
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        let turn_player_html = match self.turn_player {
-            Player::One => html! {<div class="smallblock">{"Player 1's turn"}</div>},
-            Player::Two => html! {<div class="smallblock">{"Player 2's turn"}</div>},
-        };
+    fn changed(&mut self, ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        // another saved game was opened
+        *self = Self::load(&ctx.props().name);
+        true
+    }
 
-        let status_html = match self.win_status {
-            None => turn_player_html,
-            Some(Player::One) => {
-                html! {<div class="smallblock red">{"Player 1 won!"}</div>}
-            }
-            Some(Player::Two) => {
-                html! {<div class="smallblock blue">{"Player 2 won!"}</div>}
-            }
-        };
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let status_html = status_html(&self.game);
 
         let reset_click = ctx.link().callback(|_| Msg::Reset);
-        let column_callbacks = (0..self.board.width)
+        let resign_click = ctx.link().callback(|_| Msg::Resign);
+        let undo_click = ctx.link().callback(|_| Msg::Undo);
+        let redo_click = ctx.link().callback(|_| Msg::Redo);
+        let num_players_change = ctx.link().batch_callback(|e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+            select
+                .and_then(|select| select.value().parse().ok())
+                .map(Msg::SetNumPlayers)
+        });
+        let column_callbacks = (0..self.game.board.width)
             .map(|colnr| {
                 ctx.link()
-                    .callback(move |_| Msg::ColumnClick(colnr as usize))
+                    .callback(move |_| Msg::Play(Move::Drop(colnr as usize)))
             })
             .collect::<Vec<_>>();
+        let on_roll = self
+            .game
+            .variants
+            .roll_moves
+            .then(|| ctx.link().callback(Msg::Play));
+        let pop_callbacks = self.game.variants.pop_out.then(|| {
+            (0..self.game.board.width)
+                .map(|colnr| {
+                    ctx.link()
+                        .callback(move |_| Msg::Play(Move::Pop(colnr as usize)))
+                })
+                .collect::<Vec<_>>()
+        });
+        let push_callbacks = self.game.variants.wrap_gravity.then(|| {
+            (0..self.game.board.width)
+                .map(|colnr| {
+                    ctx.link()
+                        .callback(move |_| Msg::Play(Move::DropFromBottom(colnr as usize)))
+                })
+                .collect::<Vec<_>>()
+        });
+        let variant_checkbox = |label: String, field: fn(&mut Variants) -> &mut bool| {
+            let mut variants = self.game.variants;
+            let checked = *field(&mut variants);
+            *field(&mut variants) = !checked;
+            let onchange = ctx.link().callback(move |_| Msg::SetVariants(variants));
+            html! {
+                <label class="smallblock">
+                    <input type="checkbox" {checked} {onchange}/>
+                    {label}
+                </label>
+            }
+        };
         html! { <>
             // <rect class="frame"/>
 
             {status_html}
             <div class="frame">
-            <div class="grid">
-
-            <BoardView board={self.board.clone()} winning_chips={self.winning_chips.clone()} column_callbacks={column_callbacks}/>
+            <BoardView board={self.game.board.clone()} winning_chips={self.game.winning_chips.clone()} column_callbacks={column_callbacks} pop_callbacks={pop_callbacks} push_callbacks={push_callbacks} on_roll={on_roll}
+                turn_player={self.game.win_status.is_none().then(|| self.game.turn_player.clone())} win_length={self.game.win_length as usize}
+                variants={self.game.variants}
+                moves={self.game.moves.clone()}/>
             // TODO: cloning isn't optimal. Possible solution: make board and winning_chips fields Rc<_> to allow sharing a reference
             // to the props
             </div>
-            </div>
-            <button onclick={reset_click} class="smallblock">{"Reset"}</button>
+            <button onclick={reset_click} class="smallblock">{t("game.reset")}</button>
+            <button onclick={undo_click} class="smallblock" disabled={self.game.moves.is_empty()}>{t("game.undo")}</button>
+            <button onclick={redo_click} class="smallblock" disabled={self.undone_moves.is_empty()}>{t("game.redo")}</button>
+            if !self.game.is_over() {
+                <button onclick={resign_click} class="smallblock">{t("game.resign")}</button>
+            }
+            <select class="smallblock" onchange={num_players_change}>
+                {(Player::MIN_PLAYERS..=Player::MAX_PLAYERS).map(|num_players| html! {
+                    <option value={num_players.to_string()} selected={num_players == self.game.num_players()}>
+                        {tn("players", num_players as usize, &[])}
+                    </option>
+                }).collect::<Html>()}
+            </select>
+            {variant_checkbox(t("variant.pop_out"), |variants| &mut variants.pop_out)}
+            {variant_checkbox(t("variant.roll_moves"), |variants| &mut variants.roll_moves)}
+            {variant_checkbox(t("variant.wrap_gravity"), |variants| &mut variants.wrap_gravity)}
+            <SaveAs on_save={ctx.link().callback(Msg::SaveAs)}/>
             </>
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Msg::ColumnClick(colnr) => {
-                if self.win_status.is_some() {
+            Msg::Play(mv) => {
+                if !self.play_move(mv) {
                     return false;
                 }
-                let insert = self.board.insert(colnr, &self.turn_player.clone());
-                // TODO: above we need to clone since we borrow self mutably for insert. Nice way without clone?
-                match insert {
-                    Ok(row) => {
-                        if self
-                            .board
-                            .check_win(colnr, row, &self.turn_player, self.win_length)
-                        {
-                            self.win_status = Some(self.turn_player.clone());
-                            self.winning_chips = Some(self.board.find_winning_chips(
-                                colnr,
-                                row,
-                                &self.turn_player,
-                                self.win_length,
-                            ));
-                            // log::info!("{:?}", self.winning_chips)
-                        }
-                        self.next_turn()
-                    }
-                    Err(_) => (), // do not switch turn, invalid move
-                }
+                // a new move replaces the undone ones
+                self.undone_moves.clear();
             }
             Msg::Reset => {
                 self.reset();
             }
+            Msg::Resign => {
+                self.resign();
+            }
+            Msg::SetVariants(variants) => {
+                // changing the rules starts a new game
+                self.game.variants = variants;
+                self.reset();
+            }
+            Msg::Undo => {
+                self.undo();
+            }
+            Msg::Redo => {
+                self.redo();
+            }
+            Msg::SetNumPlayers(num_players) => {
+                // changing the number of players starts a new game
+                self.restart(num_players, self.game.variants);
+            }
+            Msg::SaveAs(name) => {
+                if name.is_empty() {
+                    return false;
+                }
+                save_game(&name, self);
+                if let Some(navigator) = ctx.link().navigator() {
+                    navigator.push(&Pages::SavedLocal { name });
+                }
+                return false;
+            }
         }
+        save_game(&ctx.props().name, self);
         true
     }
 }
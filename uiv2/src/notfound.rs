@@ -1,11 +1,12 @@
+use crate::i18n::t;
 use yew::prelude::*;
 
 #[function_component]
 pub fn NotFoundPage() -> Html {
     html! {
         <>
-        <h2>{"404: Page not found"}</h2>
-        <p>{"It seems you are looking in the wrong place!"}</p>
+        <h2>{t("not_found.title")}</h2>
+        <p>{t("not_found.text")}</p>
         </>
     }
 }
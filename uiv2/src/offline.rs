@@ -0,0 +1,23 @@
+use crate::i18n::t;
+use crate::Pages;
+use yew::prelude::*;
+use yew_router::prelude::use_navigator;
+
+/// Whether the browser knows it has no connection, to tell that apart from the server failing
+pub fn is_offline() -> bool {
+    web_sys::window().is_some_and(|window| !window.navigator().on_line())
+}
+
+/// Shown on the online pages while there is no connection, they keep retrying in the background
+#[function_component(OfflineNotice)]
+pub fn offline_notice() -> Html {
+    let navigator = use_navigator().unwrap();
+    let to_local_game = Callback::from(move |_| navigator.push(&Pages::Local));
+    html! {
+        <div class="smallblock">
+            <p>{t("offline.text")}</p>
+            <p>{t("offline.local")}</p>
+            <button class="greenbutton" onclick={to_local_game}>{t("home.local_game")}</button>
+        </div>
+    }
+}
@@ -0,0 +1,320 @@
+use crate::cookies::get_player_id;
+use crate::database::get_object;
+use crate::i18n::{t, tf};
+use crate::offline::{is_offline, OfflineNotice};
+use crate::Pages;
+use common::profile::{is_hex_color, GameOutcome, PlayerInfo, Profile, ProfileUpdate};
+use common::IdType;
+use reqwasm::http::Request;
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{EventTarget, HtmlInputElement};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Colour the avatar picker starts at for players without one
+const DEFAULT_AVATAR_COLOR: &str = "#1e90ff";
+
+/// The chosen name, or a placeholder for players that never saved their profile
+pub fn display_name(info: &PlayerInfo) -> String {
+    info.display_name
+        .clone()
+        .unwrap_or_else(|| t("profile.anonymous"))
+}
+
+fn avatar(info: &PlayerInfo) -> Html {
+    // only colours from the colour picker are put in the style, anything else keeps the default
+    let style = info
+        .avatar_color
+        .as_deref()
+        .filter(|color| is_hex_color(color))
+        .map(|color| format!("background-color: {}", color));
+    html! { <span class="avatar" {style}/> }
+}
+
+/// Avatar and name of a player, linking to their profile
+pub fn player_link(info: &PlayerInfo) -> Html {
+    html! {
+        <Link<Pages> classes="playername" to={Pages::Profile { player_id: info.player_id }}>
+            {avatar(info)}
+            {display_name(info)}
+        </Link<Pages>>
+    }
+}
+
+/// Names and avatar colours of some players, see `use_player_infos`
+pub struct PlayerInfos(HashMap<IdType, PlayerInfo>);
+
+impl PlayerInfos {
+    /// `player_link` for the player, shown as anonymous until their info is fetched
+    pub fn link(&self, player_id: IdType) -> Html {
+        match self.0.get(&player_id) {
+            Some(info) => player_link(info),
+            None => player_link(&PlayerInfo::anonymous(player_id)),
+        }
+    }
+}
+
+/// Fetches the names and avatar colours of `player_ids` in a single request, and again when the
+/// ids change
+#[hook]
+pub fn use_player_infos(player_ids: Vec<IdType>) -> PlayerInfos {
+    let infos = use_state(HashMap::new);
+    {
+        let infos = infos.clone();
+        use_effect_with_deps(
+            move |player_ids: &Vec<IdType>| {
+                let ids = player_ids
+                    .iter()
+                    .map(IdType::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                if !ids.is_empty() {
+                    spawn_local(async move {
+                        if let Ok(fetched) =
+                            get_object::<Vec<PlayerInfo>>(&format!("/api/players/{}", ids)).await
+                        {
+                            infos.set(
+                                fetched
+                                    .into_iter()
+                                    .map(|info| (info.player_id, info))
+                                    .collect(),
+                            );
+                        }
+                    });
+                }
+                || ()
+            },
+            player_ids,
+        );
+    }
+    PlayerInfos((*infos).clone())
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PlayerNameProps {
+    pub player_id: IdType,
+}
+
+/// `player_link` for a player of whom only the id is known, fetches their name and avatar colour
+#[function_component(PlayerName)]
+pub fn player_name(props: &PlayerNameProps) -> Html {
+    use_player_infos(vec![props.player_id]).link(props.player_id)
+}
+
+#[derive(PartialEq, Properties)]
+struct ProfileEditorProps {
+    info: PlayerInfo,
+    on_saved: Callback<PlayerInfo>,
+}
+
+/// Form for a player to change their own name and avatar colour
+#[function_component(ProfileEditor)]
+fn profile_editor(props: &ProfileEditorProps) -> Html {
+    let name = use_state(|| props.info.display_name.clone().unwrap_or_default());
+    let color = use_state(|| {
+        props
+            .info
+            .avatar_color
+            .clone()
+            .unwrap_or_else(|| DEFAULT_AVATAR_COLOR.to_owned())
+    });
+    let use_color = use_state(|| props.info.avatar_color.is_some());
+    let message = use_state(|| None::<String>);
+
+    let input_value = |e: &Event| {
+        let target: Option<EventTarget> = e.target();
+        target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+    };
+    let name_change = {
+        let name = name.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = input_value(&e) {
+                name.set(input.value());
+            }
+        })
+    };
+    let color_change = {
+        let color = color.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = input_value(&e) {
+                color.set(input.value());
+            }
+        })
+    };
+    let use_color_change = {
+        let use_color = use_color.clone();
+        Callback::from(move |_| use_color.set(!*use_color))
+    };
+    let save = {
+        let message = message.clone();
+        let player_id = props.info.player_id;
+        let on_saved = props.on_saved.clone();
+        let update = ProfileUpdate {
+            display_name: name.trim().to_owned(),
+            avatar_color: use_color.then(|| (*color).clone()),
+        };
+        Callback::from(move |_| {
+            if !update.is_valid() {
+                message.set(Some(t("profile.invalid")));
+                return;
+            }
+            let message = message.clone();
+            let on_saved = on_saved.clone();
+            let update = update.clone();
+            spawn_local(async move {
+                match Request::post("/api/profile")
+                    .body(serde_json::to_string(&update).unwrap())
+                    .send()
+                    .await
+                {
+                    Ok(response) if response.ok() => {
+                        message.set(Some(t("profile.saved")));
+                        on_saved.emit(PlayerInfo {
+                            player_id,
+                            display_name: Some(update.display_name),
+                            avatar_color: update.avatar_color,
+                        });
+                    }
+                    _ if is_offline() => message.set(Some(t("home.offline"))),
+                    _ => message.set(Some(t("profile.save_failed"))),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="smallblock">
+            <label>
+                {t("profile.name")}
+                {" "}
+                <input type="text" value={(*name).clone()} onchange={name_change}
+                    maxlength={ProfileUpdate::MAX_NAME_LENGTH.to_string()}/>
+            </label>
+            <label>
+                <input type="checkbox" checked={*use_color} onchange={use_color_change}/>
+                {t("profile.use_color")}
+                {" "}
+                <input type="color" value={(*color).clone()} onchange={color_change} disabled={!*use_color}/>
+            </label>
+            <button class="greenbutton" onclick={save}>{t("profile.save")}</button>
+            if let Some(message) = &*message {
+                <p>{message}</p>
+            }
+        </div>
+    }
+}
+
+fn outcome_text(outcome: GameOutcome) -> String {
+    t(match outcome {
+        GameOutcome::Won => "profile.won",
+        GameOutcome::Lost => "profile.lost",
+        GameOutcome::Draw => "profile.draw",
+    })
+}
+
+fn profile_html(profile: &Profile, on_saved: Callback<PlayerInfo>) -> Html {
+    let own = profile.info.player_id == get_player_id();
+    let history_html = profile
+        .history
+        .iter()
+        .map(|game| {
+            let opponents = game
+                .opponents
+                .iter()
+                .map(display_name)
+                .collect::<Vec<_>>()
+                .join(", ");
+            html! {
+                <li>{tf("profile.history_entry", &[
+                    &game.finished_on,
+                    &game.game_name,
+                    &outcome_text(game.outcome),
+                    &opponents,
+                    &format!("{:+}", game.rating_change),
+                ])}</li>
+            }
+        })
+        .collect::<Html>();
+    let record = &profile.record;
+
+    html! {
+        <>
+            <h2>{avatar(&profile.info)}{display_name(&profile.info)}</h2>
+            if own {
+                <ProfileEditor info={profile.info.clone()} {on_saved}/>
+            }
+            <p>{tf("profile.rating", &[&profile.rating])}</p>
+            <p>{tf("profile.record", &[&record.wins, &record.losses, &record.draws])}</p>
+            <h3>{t("profile.history")}</h3>
+            if profile.history.is_empty() {
+                <p>{t("profile.no_games")}</p>
+            } else {
+                <ul class="history">{history_html}</ul>
+            }
+        </>
+    }
+}
+
+#[derive(PartialEq)]
+enum FetchProfile {
+    Fetching,
+    Success(Profile),
+    Failed,
+    Offline,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ProfileProps {
+    /// Whose profile to show, the own profile if none
+    #[prop_or_default]
+    pub player_id: Option<IdType>,
+}
+
+/// A player's rating, win/loss/draw record and recent games. Players can edit their own name and
+/// avatar colour here.
+#[function_component(ProfilePage)]
+pub fn profile_page(props: &ProfileProps) -> Html {
+    let player_id = props.player_id.unwrap_or_else(get_player_id);
+    let fetch_state = use_state(|| FetchProfile::Fetching);
+    {
+        let fetch_state = fetch_state.clone();
+        use_effect_with_deps(
+            move |player_id| {
+                let player_id = *player_id;
+                fetch_state.set(FetchProfile::Fetching);
+                spawn_local(async move {
+                    match get_object(&format!("/api/profile/{}", player_id)).await {
+                        Ok(profile) => fetch_state.set(FetchProfile::Success(profile)),
+                        Err(_) if is_offline() => fetch_state.set(FetchProfile::Offline),
+                        Err(_) => fetch_state.set(FetchProfile::Failed),
+                    }
+                });
+                || ()
+            },
+            player_id,
+        );
+    }
+    let on_saved = {
+        let fetch_state = fetch_state.clone();
+        Callback::from(move |info: PlayerInfo| {
+            if let FetchProfile::Success(profile) = &*fetch_state {
+                fetch_state.set(FetchProfile::Success(Profile {
+                    info,
+                    ..profile.clone()
+                }));
+            }
+        })
+    };
+
+    let body_html = match &*fetch_state {
+        FetchProfile::Fetching => html! {t("lobby.wait")},
+        FetchProfile::Success(profile) => profile_html(profile, on_saved),
+        FetchProfile::Failed => html! {t("profile.failed")},
+        FetchProfile::Offline => html! {<OfflineNotice/>},
+    };
+    html! {
+        <div class="mainpage">{body_html}</div>
+    }
+}
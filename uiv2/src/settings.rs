@@ -0,0 +1,175 @@
+use crate::cell::Cell;
+use crate::i18n::{t, LanguagePicker};
+use crate::storage;
+use common::Player;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{EventTarget, HtmlSelectElement};
+use yew::prelude::*;
+
+const STORAGE_KEY: &str = "settings";
+
+/// Colours of the players' chips
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    /// Okabe-Ito colours, safe for the common kinds of colour blindness
+    OkabeIto,
+    /// Paul Tol's bright scheme, also colour blind safe
+    TolBright,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Classic, Palette::OkabeIto, Palette::TolBright];
+
+    fn label(&self) -> String {
+        t(match self {
+            Palette::Classic => "settings.classic",
+            Palette::OkabeIto => "settings.okabe_ito",
+            Palette::TolBright => "settings.tol_bright",
+        })
+    }
+
+    /// Class on the root element that swaps the colour variables, see headerconnect.css
+    fn class(&self) -> Option<&'static str> {
+        match self {
+            Palette::Classic => None,
+            Palette::OkabeIto => Some("okabeito"),
+            Palette::TolBright => Some("tolbright"),
+        }
+    }
+}
+
+/// Display preferences of this browser, kept in local storage
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub palette: Palette,
+    #[serde(default)]
+    pub dark_mode: bool,
+    /// Draws a symbol on each player's chips, so they can be told apart without colour
+    #[serde(default)]
+    pub chip_patterns: bool,
+    /// Turns off the animations, starts from the browser's preference until it is changed
+    #[serde(default = "prefers_reduced_motion")]
+    pub reduce_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            palette: Palette::default(),
+            dark_mode: false,
+            chip_patterns: false,
+            reduce_motion: prefers_reduced_motion(),
+        }
+    }
+}
+
+/// Whether the browser asks for as little animation as possible
+fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|window| window.match_media("(prefers-reduced-motion: reduce)").ok())
+        .flatten()
+        .is_some_and(|query| query.matches())
+}
+
+impl Settings {
+    /// The saved settings, or the defaults if nothing was saved
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(STORAGE_KEY, self);
+    }
+
+    /// Sets the theme classes on the root element, the stylesheets do the rest
+    pub fn apply(&self) {
+        let Some(root) = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.document_element())
+        else {
+            return;
+        };
+        let classes = [
+            self.palette.class(),
+            self.dark_mode.then_some("dark"),
+            self.chip_patterns.then_some("patterns"),
+            self.reduce_motion.then_some("reducedmotion"),
+        ];
+        root.set_class_name(&classes.into_iter().flatten().collect::<Vec<_>>().join(" "));
+    }
+}
+
+#[function_component(SettingsPage)]
+pub fn settings_page() -> Html {
+    let settings = use_state(Settings::load);
+    let update = {
+        let settings = settings.clone();
+        move |change: fn(&mut Settings, &Event)| {
+            let settings = settings.clone();
+            Callback::from(move |e: Event| {
+                let mut new_settings = (*settings).clone();
+                change(&mut new_settings, &e);
+                new_settings.save();
+                new_settings.apply();
+                settings.set(new_settings);
+            })
+        }
+    };
+    let palette_change = update(|settings, e| {
+        let target: Option<EventTarget> = e.target();
+        let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+        if let Some(palette) = select
+            .and_then(|select| select.value().parse::<usize>().ok())
+            .and_then(|index| Palette::ALL.get(index))
+        {
+            settings.palette = *palette;
+        }
+    });
+    let dark_mode_change = update(|settings, _| settings.dark_mode = !settings.dark_mode);
+    let chip_patterns_change =
+        update(|settings, _| settings.chip_patterns = !settings.chip_patterns);
+    let reduce_motion_change =
+        update(|settings, _| settings.reduce_motion = !settings.reduce_motion);
+
+    html! {
+        <div class="mainpage">
+            <h2>{t("settings.title")}</h2>
+            <LanguagePicker/>
+            <label class="smallblock">
+                {t("settings.colours")}
+                {" "}
+                <select onchange={palette_change}>
+                    {Palette::ALL.iter().enumerate().map(|(index, palette)| html! {
+                        <option value={index.to_string()} selected={*palette == settings.palette}>
+                            {palette.label()}
+                        </option>
+                    }).collect::<Html>()}
+                </select>
+            </label>
+            <label class="smallblock">
+                <input type="checkbox" checked={settings.dark_mode} onchange={dark_mode_change}/>
+                {t("settings.dark_mode")}
+            </label>
+            <label class="smallblock">
+                <input type="checkbox" checked={settings.chip_patterns} onchange={chip_patterns_change}/>
+                {t("settings.chip_patterns")}
+            </label>
+            <label class="smallblock">
+                <input type="checkbox" checked={settings.reduce_motion} onchange={reduce_motion_change}/>
+                {t("settings.reduce_motion")}
+            </label>
+            <div class="frame">
+                <div class="grid" style={format!("grid-template-columns: repeat({}, fit-content(100%));", Player::ALL.len() + 1)}>
+                    {Player::ALL.iter().enumerate().map(|(index, player)| html! {
+                        <Cell status={Some(player.clone())} winning={false} coords={(index, 0)}/>
+                    }).collect::<Html>()}
+                    <Cell status={Some(Player::One)} winning={true} coords={(Player::ALL.len(), 0)}/>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,28 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use web_sys::Storage;
+
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Value saved under `key` in the browser's local storage, `None` if there is none or it can't be read
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = local_storage()?.get_item(key).ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+/// Saves `value` as json under `key` in the browser's local storage
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    match serde_json::to_string(value) {
+        Ok(json) => {
+            if storage.set_item(key, &json).is_err() {
+                log::info!("Failed to save {} in local storage", key);
+            }
+        }
+        Err(err) => log::info!("Failed to serialize {}: {}", key, err),
+    }
+}
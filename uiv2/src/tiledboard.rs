@@ -0,0 +1,143 @@
+use crate::cell::cell_svg;
+use crate::drag::use_drag;
+use crate::i18n::t;
+use common::board::Board;
+use std::collections::HashSet;
+use yew::prelude::*;
+
+/// Number of copies of the board shown next to each other, and above each other
+const COPIES: i32 = 3;
+/// Side length of a cell in svg units, see `cell_svg`
+const CELL: i32 = 100;
+
+#[derive(PartialEq, Properties)]
+pub struct TiledBoardProps {
+    pub board: Board,
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    pub win_length: usize,
+    pub column_callbacks: Vec<Callback<MouseEvent>>,
+}
+
+/// Board view for the torus: the board is tiled 3x3 times so lines that wrap around can be seen in
+/// one piece. The middle copy is the board itself, dragging pans it around the torus. Only drops
+/// can be played from this view, clicking a column in any copy drops a chip in it.
+#[function_component(TiledBoardView)]
+pub fn tiled_board_view(props: &TiledBoardProps) -> Html {
+    // columns and rows the view is panned by
+    let offset = use_state(|| (0, 0));
+
+    let width = props.board.width as i32;
+    let height = props.board.height as i32;
+    let (columns, rows) = (width * COPIES, height * COPIES);
+    let drag = use_drag(offset.clone(), move |start, (dx, dy), svg| {
+        // the svg is scaled to fit the page, so convert pixels to svg units first
+        let scale = (columns * CELL) as f64 / svg.client_width().max(1) as f64;
+        let cells = |pixels: i32| (pixels as f64 * scale / CELL as f64).round() as i32;
+        let (dx, dy) = (cells(dx), cells(dy));
+        (dx != 0 || dy != 0).then_some((start.0 - dx, start.1 + dy))
+    });
+    let (offset_x, offset_y) = *offset;
+    // board cell shown at (x, y) counted from the top left, rows on the board count from the bottom
+    let cell_at = move |x: i32, y: i32| {
+        (
+            (x + offset_x).rem_euclid(width) as usize,
+            (rows - 1 - y + offset_y).rem_euclid(height) as usize,
+        )
+    };
+    let winning = |x: i32, y: i32| {
+        (0..columns).contains(&x)
+            && (0..rows).contains(&y)
+            && props
+                .winning_chips
+                .as_ref()
+                .is_some_and(|chips| chips.contains(&cell_at(x, y)))
+    };
+
+    let columns_html = (0..columns)
+        .map(|x| {
+            let on_column_click = props.column_callbacks[cell_at(x, 0).0].clone();
+            let dragged = drag.dragged.clone();
+            let onclick = Callback::from(move |e: MouseEvent| {
+                if !dragged.replace(false) {
+                    on_column_click.emit(e);
+                }
+            });
+            let cells_html = (0..rows)
+                .map(|y| {
+                    let (column, row) = cell_at(x, y);
+                    html! {
+                        <g transform={format!("translate({} {})", x * CELL, y * CELL)}>
+                            {cell_svg(props.board.board[column][row].as_ref(), winning(x, y), None, None)}
+                        </g>
+                    }
+                })
+                .collect::<Html>();
+            html! { <g {onclick}>{cells_html}</g> }
+        })
+        .collect::<Html>();
+
+    // one segment for each run of winning chips that is part of a line in its direction, the
+    // copies make lines across the edges continuous
+    let in_line = |x: i32, y: i32, (dx, dy): (i32, i32)| {
+        let (column, row) = cell_at(x, y);
+        // rows on the board count from the bottom, so the direction flips vertically
+        props.board.board[column][row]
+            .as_ref()
+            .is_some_and(|player| {
+                props
+                    .board
+                    .find_adjacent_chips_in_dir(column, row, (dx, -dy), player)
+                    .len()
+                    >= props.win_length
+            })
+    };
+    let centre = |i: i32| (i * CELL + CELL / 2).to_string();
+    let lines_html = [(1, 0), (0, 1), (1, 1), (1, -1)]
+        .into_iter()
+        .flat_map(|(dx, dy)| {
+            (0..columns)
+                .flat_map(move |x| (0..rows).map(move |y| (x, y)))
+                .filter(move |&(x, y)| winning(x, y) && !winning(x - dx, y - dy))
+                .map(move |(x, y)| {
+                    let length = (1..).find(|&s| !winning(x + s * dx, y + s * dy)).unwrap();
+                    (x, y, dx, dy, length - 1)
+                })
+        })
+        .filter(|&(x, y, dx, dy, steps)| steps > 0 && in_line(x, y, (dx, dy)))
+        .map(|(x, y, dx, dy, steps)| {
+            let (x2, y2) = (x + steps * dx, y + steps * dy);
+            html! {
+                <line class="winningline" x1={centre(x)} y1={centre(y)} x2={centre(x2)} y2={centre(y2)}/>
+            }
+        })
+        .collect::<Html>();
+
+    // fade the copies around the board in the middle
+    let (board_width, board_height) = (width * CELL, height * CELL);
+    let fade_path = format!(
+        "M 0 0 h {w} v {h} h -{w} Z M {bw} {bh} h {bw} v {bh} h -{bw} Z",
+        w = columns * CELL,
+        h = rows * CELL,
+        bw = board_width,
+        bh = board_height,
+    );
+
+    let recentre = Callback::from(move |_| offset.set((0, 0)));
+
+    html! {
+        <>
+            <svg class="tiledboard" viewBox={format!("0 0 {} {}", columns * CELL, rows * CELL)}
+                onpointerdown={drag.onpointerdown} onpointermove={drag.onpointermove} onpointerup={drag.onpointerup}
+                onpointercancel={drag.onpointerleave.clone()} onpointerleave={drag.onpointerleave}>
+                {columns_html}
+                <path class="tilefade" d={fade_path}/>
+                <rect class="fundamentaldomain" x={board_width.to_string()} y={board_height.to_string()}
+                    width={board_width.to_string()} height={board_height.to_string()}/>
+                {lines_html}
+            </svg>
+            if (offset_x, offset_y) != (0, 0) {
+                <button class="popbutton" onclick={recentre}>{t("board.recentre")}</button>
+            }
+        </>
+    }
+}
@@ -0,0 +1,155 @@
+use crate::cell::player_color;
+use crate::drag::use_drag;
+use common::board::Board;
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use yew::prelude::*;
+
+/// Distance from the centre of the torus to the centre of the tube
+const MAJOR_RADIUS: f64 = 300.;
+/// Radius of the tube
+const MINOR_RADIUS: f64 = 130.;
+/// Distance of the camera to the centre, for the perspective
+const CAMERA_DISTANCE: f64 = 1800.;
+/// Radians the torus turns per pixel dragged
+const DRAG_SPEED: f64 = 0.01;
+/// Part of a cell's sides taken up by the chip, the rest is board
+const CHIP_INSET: f64 = 0.15;
+
+#[derive(PartialEq, Properties)]
+pub struct TorusProps {
+    pub board: Board,
+    pub winning_chips: Option<HashSet<(usize, usize)>>,
+    pub column_callbacks: Vec<Callback<MouseEvent>>,
+}
+
+type Point = [f64; 3];
+
+/// Angles the torus is shown at: `spin` around its own axis, `tilt` towards the viewer
+#[derive(PartialEq, Clone, Copy)]
+struct Rotation {
+    spin: f64,
+    tilt: f64,
+}
+
+impl Rotation {
+    fn apply(&self, [x, y, z]: Point) -> Point {
+        let (x, y) = (
+            x * self.spin.cos() - y * self.spin.sin(),
+            x * self.spin.sin() + y * self.spin.cos(),
+        );
+        let (y, z) = (
+            y * self.tilt.cos() - z * self.tilt.sin(),
+            y * self.tilt.sin() + z * self.tilt.cos(),
+        );
+        [x, y, z]
+    }
+}
+
+/// Point on the torus surface and its normal. Columns go around the hole (`u`), rows around the
+/// tube (`v`), both in cells of the board.
+fn surface(board: &Board, u: f64, v: f64) -> (Point, Point) {
+    let u = 2. * PI * u / board.width as f64;
+    let v = 2. * PI * v / board.height as f64;
+    let normal = [v.cos() * u.cos(), v.cos() * u.sin(), v.sin()];
+    let ring = MAJOR_RADIUS + MINOR_RADIUS * v.cos();
+    (
+        [ring * u.cos(), ring * u.sin(), MINOR_RADIUS * v.sin()],
+        normal,
+    )
+}
+
+/// Screen coordinates of a rotated point, the camera looks down the z axis
+fn project([x, y, z]: Point) -> String {
+    let perspective = CAMERA_DISTANCE / (CAMERA_DISTANCE - z);
+    format!("{:.1},{:.1}", x * perspective, -y * perspective)
+}
+
+/// Polygon for the part of the surface between (u0, v0) and (u1, v1)
+fn patch(board: &Board, rotation: Rotation, (u0, v0): (f64, f64), (u1, v1): (f64, f64)) -> String {
+    [(u0, v0), (u1, v0), (u1, v1), (u0, v1)]
+        .iter()
+        .map(|&(u, v)| project(rotation.apply(surface(board, u, v).0)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The board drawn on a torus that can be turned by dragging it. It is plain svg, projected in
+/// the browser. Clicking a cell drops a chip in its column.
+#[function_component(TorusView)]
+pub fn torus_view(props: &TorusProps) -> Html {
+    let rotation = use_state(|| Rotation {
+        spin: 0.,
+        tilt: -1.,
+    });
+    let drag = use_drag(rotation.clone(), |start: &Rotation, (dx, dy), _| {
+        let (dx, dy) = (dx as f64, dy as f64);
+        // a few pixels of jitter still count as a click
+        (dx.abs() + dy.abs() > 5.).then(|| Rotation {
+            spin: start.spin - dx * DRAG_SPEED,
+            tilt: (start.tilt + dy * DRAG_SPEED).clamp(-PI, 0.),
+        })
+    });
+
+    let board = &props.board;
+    let light = [-0.3, 0.5, 0.8];
+    let mut cells = Vec::new();
+    for (column, cells_in_column) in board.board.iter().enumerate() {
+        for (row, status) in cells_in_column.iter().enumerate() {
+            let (u, v) = (column as f64, row as f64);
+            let (centre, normal) = surface(board, u + 0.5, v + 0.5);
+            let normal = rotation.apply(normal);
+            // cells on the far side face away from the camera
+            if normal[2] <= 0. {
+                continue;
+            }
+            let depth = rotation.apply(centre)[2];
+            let shade: f64 = normal.iter().zip(light).map(|(n, l)| n * l).sum();
+            let winning = props
+                .winning_chips
+                .as_ref()
+                .is_some_and(|chips| chips.contains(&(column, row)));
+            let chip_fill = match status {
+                Some(_) if winning => "var(--winning)".to_owned(),
+                Some(player) => format!("var(--{})", player_color(player)),
+                None => "var(--background-color)".to_owned(),
+            };
+            let on_column_click = props.column_callbacks[column].clone();
+            let dragged = drag.dragged.clone();
+            let onclick = Callback::from(move |e: MouseEvent| {
+                if !dragged.replace(false) {
+                    on_column_click.emit(e);
+                }
+            });
+            let chip_corners = (
+                (u + CHIP_INSET, v + CHIP_INSET),
+                (u + 1. - CHIP_INSET, v + 1. - CHIP_INSET),
+            );
+            let darkness = format!("{:.2}", (1. - shade.max(0.)) * 0.6);
+            let html = html! {
+                <g {onclick}>
+                    <polygon class="torusboard" points={patch(board, *rotation, (u, v), (u + 1., v + 1.))}/>
+                    <polygon points={patch(board, *rotation, chip_corners.0, chip_corners.1)} fill={chip_fill}
+                        class={classes!(winning.then_some("toruswinning"))}/>
+                    <polygon class="torusshade" points={patch(board, *rotation, (u, v), (u + 1., v + 1.))}
+                        fill-opacity={darkness}/>
+                </g>
+            };
+            cells.push((depth, html));
+        }
+    }
+    // paint from back to front
+    cells.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    let cells_html = cells.into_iter().map(|(_, html)| html).collect::<Html>();
+
+    // room for the torus seen from any angle, with the perspective enlarging the near side
+    let outer_radius = MAJOR_RADIUS + MINOR_RADIUS;
+    let size = outer_radius * CAMERA_DISTANCE / (CAMERA_DISTANCE - outer_radius);
+    html! {
+        <svg class="torusview" viewBox={format!("{0} {0} {1} {1}", -size, 2. * size)}
+            onpointerdown={drag.onpointerdown} onpointermove={drag.onpointermove} onpointerup={drag.onpointerup}
+            onpointercancel={drag.onpointerleave.clone()} onpointerleave={drag.onpointerleave}>
+            {cells_html}
+        </svg>
+    }
+}
@@ -0,0 +1,48 @@
+// Service worker that lets the app load without a connection. Local games need nothing else,
+// the online pages show an offline notice when the api can't be reached.
+const CACHE = "connect4-torus-v1";
+
+// Trunk gives the built files hashed names, so the app shell is found through index.html
+async function cacheAppShell() {
+    const cache = await caches.open(CACHE);
+    const index = await fetch("/");
+    const html = await index.clone().text();
+    await cache.put("/", index);
+    const assets = [...html.matchAll(/["'](\/[^"'\/][^"']*\.(?:js|wasm|css|ico|svg|json))["']/g)]
+        .map(match => match[1]);
+    await cache.addAll([...new Set(assets)]);
+}
+
+self.addEventListener("install", event => {
+    event.waitUntil(cacheAppShell().then(() => self.skipWaiting()));
+});
+
+self.addEventListener("activate", event => {
+    // drop the caches of older versions of this worker
+    event.waitUntil(
+        caches.keys()
+            .then(keys => Promise.all(keys.filter(key => key !== CACHE).map(key => caches.delete(key))))
+            .then(() => self.clients.claim())
+    );
+});
+
+self.addEventListener("fetch", event => {
+    const url = new URL(event.request.url);
+    // the api is never cached, the pages handle failed requests themselves
+    if (event.request.method !== "GET" || url.origin !== self.location.origin || url.pathname.startsWith("/api/")) {
+        return;
+    }
+    // every page is index.html, the router picks the page
+    const key = event.request.mode === "navigate" ? "/" : event.request;
+    event.respondWith(
+        fetch(event.request)
+            .then(response => {
+                if (response.ok) {
+                    const copy = response.clone();
+                    caches.open(CACHE).then(cache => cache.put(key, copy));
+                }
+                return response;
+            })
+            .catch(() => caches.match(key).then(cached => cached || Response.error()))
+    );
+});